@@ -0,0 +1,271 @@
+//! On-disk caching of endpoint responses
+//!
+//! A [ResponseCache] stores the data returned by endpoints in a directory,
+//! keyed by a hash of the endpoint path and query parameters. How long an
+//! entry stays fresh is decided by each [Endpoint](crate::endpoint::Endpoint),
+//! e.g. forever for the results of a subsession, and a day for the list of
+//! cars. These can be overridden per endpoint with [ResponseCache::ttl].
+//!
+//! ```no_run
+//! # async fn example() -> Result<(), iracing_api::client::ClientInitError> {
+//! use iracing_api::{cache::{CacheMode, ResponseCache}, IracingApiClient};
+//!
+//! let client = IracingApiClient::builder()
+//!     .credentials("email@example.com", "password")
+//!     .cache(ResponseCache::new("cache").mode(CacheMode::CacheOnly))
+//!     .build()
+//!     .await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::{
+    collections::HashMap,
+    fmt::Write,
+    fs, io,
+    path::PathBuf,
+    time::{Duration, SystemTime},
+};
+use {
+    reqwest::Url,
+    sha2::{Digest, Sha256},
+};
+
+/// How long a cached response stays fresh
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CacheTtl {
+    /// The data never changes, e.g. the results of a finished subsession
+    Forever,
+    /// The data is refreshed once it is older than the duration
+    For(Duration),
+}
+
+impl CacheTtl {
+    /// A TTL of one day, for static data such as cars and tracks
+    pub const DAY: CacheTtl = CacheTtl::For(Duration::from_secs(24 * 60 * 60));
+
+    fn is_fresh(self, age: Duration) -> bool {
+        match self {
+            CacheTtl::Forever => true,
+            CacheTtl::For(ttl) => age < ttl,
+        }
+    }
+}
+
+/// How the client uses its [ResponseCache]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CacheMode {
+    /// Serve fresh entries from the cache, and store new responses
+    ReadWrite,
+    /// Never send requests, and serve every entry found in the cache, even
+    /// expired ones or those of endpoints that are not normally cached. A
+    /// missing entry is reported as [ApiError::CacheMiss](crate::client::ApiError::CacheMiss).
+    CacheOnly,
+    /// Always send requests, but still store new responses
+    Bypass,
+}
+
+/// A directory of cached endpoint responses
+#[derive(Debug, Clone)]
+pub struct ResponseCache {
+    dir: PathBuf,
+    mode: CacheMode,
+    ttls: HashMap<String, Option<CacheTtl>>,
+}
+
+impl ResponseCache {
+    /// Cache responses in `dir`, which is created if needed
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            mode: CacheMode::ReadWrite,
+            ttls: HashMap::new(),
+        }
+    }
+
+    /// Defaults to [CacheMode::ReadWrite]
+    pub fn mode(mut self, mode: CacheMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Override the TTL of the endpoint at `path`, e.g. `/data/car/get`.
+    /// `None` disables caching for the endpoint.
+    pub fn ttl(mut self, path: &str, ttl: Option<CacheTtl>) -> Self {
+        self.ttls.insert(path.to_string(), ttl);
+        self
+    }
+
+    pub(crate) fn current_mode(&self) -> CacheMode {
+        self.mode
+    }
+
+    /// The TTL of the endpoint at `path`, given the endpoint's own TTL
+    pub(crate) fn ttl_for(&self, path: &str, default: Option<CacheTtl>) -> Option<CacheTtl> {
+        self.ttls.get(path).copied().unwrap_or(default)
+    }
+
+    /// Read the cached data returned by `url`, or `None` if there is no entry,
+    /// or if it is older than `ttl`
+    pub(crate) fn get(&self, url: &Url, ttl: Option<CacheTtl>) -> io::Result<Option<Vec<u8>>> {
+        let path = self.entry_path(url);
+        let modified = match fs::metadata(&path) {
+            Ok(metadata) => metadata.modified()?,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(err),
+        };
+        let age = SystemTime::now()
+            .duration_since(modified)
+            .unwrap_or_default();
+        if let Some(ttl) = ttl {
+            if !ttl.is_fresh(age) {
+                return Ok(None);
+            }
+        }
+        fs::read(path).map(Some)
+    }
+
+    /// Store the data returned by `url`
+    ///
+    /// The data is written to a temporary file first, and then moved into
+    /// place, so that concurrent readers never see a partially written entry.
+    pub(crate) fn put(&self, url: &Url, body: &[u8]) -> io::Result<()> {
+        let path = self.entry_path(url);
+        fs::create_dir_all(&self.dir)?;
+        let temp_path = path.with_extension(format!("{:016x}.tmp", rand::random::<u64>()));
+        fs::write(&temp_path, body)?;
+        if let Err(err) = fs::rename(&temp_path, path) {
+            let _ = fs::remove_file(&temp_path);
+            return Err(err);
+        }
+        Ok(())
+    }
+
+    /// The file caching the data returned by `url`, named after a hash of its
+    /// path and sorted query parameters. The host is ignored.
+    fn entry_path(&self, url: &Url) -> PathBuf {
+        let mut query: Vec<_> = url.query_pairs().collect();
+        query.sort();
+
+        let mut hasher = Sha256::new();
+        hasher.update(url.path());
+        for (name, value) in query {
+            hasher.update([0]);
+            hasher.update(name.as_bytes());
+            hasher.update([0]);
+            hasher.update(value.as_bytes());
+        }
+        let mut file_name = String::with_capacity(69);
+        for byte in hasher.finalize() {
+            let _ = write!(file_name, "{:02x}", byte);
+        }
+        file_name.push_str(".json");
+        self.dir.join(file_name)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{CacheTtl, ResponseCache};
+    use reqwest::Url;
+    use std::{fs, time::Duration};
+
+    fn url() -> Url {
+        Url::parse("https://members-ng.iracing.com/data/results/get?subsession_id=1").unwrap()
+    }
+
+    #[test]
+    fn entry_path_is_unique_per_query() {
+        let cache = ResponseCache::new("cache");
+        let path = |url: &str| cache.entry_path(&Url::parse(url).unwrap());
+
+        assert_eq!(
+            path("https://members-ng.iracing.com/data/results/get?a=1&b=2"),
+            path("http://localhost/data/results/get?b=2&a=1")
+        );
+        // Distinct queries get distinct hashes, even when they only differ in
+        // characters that are not allowed in file names or in escaping
+        assert_ne!(
+            path("https://members-ng.iracing.com/data/lookup/drivers?search_term=a/b"),
+            path("https://members-ng.iracing.com/data/lookup/drivers?search_term=a_b")
+        );
+        assert_ne!(
+            path("https://members-ng.iracing.com/data/results/get?a=1%262"),
+            path("https://members-ng.iracing.com/data/results/get?a=1&2")
+        );
+
+        let long_term = "x".repeat(1000);
+        let long = path(&format!(
+            "https://members-ng.iracing.com/data/lookup/drivers?search_term={}",
+            long_term
+        ));
+        assert_eq!(long.file_name().unwrap().len(), 69);
+    }
+
+    #[test]
+    fn put_replaces_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = ResponseCache::new(dir.path());
+        cache.put(&url(), b"[1]").unwrap();
+        cache.put(&url(), b"[2]").unwrap();
+        assert_eq!(
+            cache.get(&url(), None).unwrap().as_deref(),
+            Some(&b"[2]"[..])
+        );
+        // No temporary files are left behind
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn get_returns_fresh_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = ResponseCache::new(dir.path());
+        assert_eq!(cache.get(&url(), Some(CacheTtl::Forever)).unwrap(), None);
+
+        cache.put(&url(), b"[]").unwrap();
+        assert_eq!(
+            cache
+                .get(&url(), Some(CacheTtl::Forever))
+                .unwrap()
+                .as_deref(),
+            Some(&b"[]"[..])
+        );
+        assert_eq!(
+            cache.get(&url(), Some(CacheTtl::DAY)).unwrap().as_deref(),
+            Some(&b"[]"[..])
+        );
+        assert_eq!(
+            cache.get(&url(), None).unwrap().as_deref(),
+            Some(&b"[]"[..])
+        );
+    }
+
+    #[test]
+    fn get_ignores_expired_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = ResponseCache::new(dir.path());
+        cache.put(&url(), b"[]").unwrap();
+        assert_eq!(
+            cache
+                .get(&url(), Some(CacheTtl::For(Duration::ZERO)))
+                .unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn ttl_overrides() {
+        let cache = ResponseCache::new("cache")
+            .ttl("/data/car/get", None)
+            .ttl("/data/track/get", Some(CacheTtl::Forever));
+        assert_eq!(cache.ttl_for("/data/car/get", Some(CacheTtl::DAY)), None);
+        assert_eq!(
+            cache.ttl_for("/data/track/get", None),
+            Some(CacheTtl::Forever)
+        );
+        assert_eq!(
+            cache.ttl_for("/data/results/get", Some(CacheTtl::Forever)),
+            Some(CacheTtl::Forever)
+        );
+    }
+}
@@ -1,17 +1,100 @@
-use crate::model::{auth::*, *};
-use std::collections::HashMap;
-use {eyre::Result, reqwest::Client, serde::Deserialize, thiserror::Error};
+use crate::{
+    cache::{CacheMode, CacheTtl, ResponseCache},
+    endpoint::{decode, Chunked, ChunkedEndpoint, Endpoint, Envelope, QueryError},
+    model::{auth::*, chunk_info::ChunkInfo, *},
+    rate_limit::{RateLimit, RateLimiter},
+    retry::RetryPolicy,
+    transport::{Request, Response, Transport, TransportError, TransportErrorKind},
+};
+use std::{
+    collections::{BTreeSet, HashMap},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, RwLock,
+    },
+};
+use {
+    chrono::{DateTime, Utc},
+    futures::{future, stream, Stream, StreamExt, TryStreamExt},
+    reqwest::{
+        header::{HeaderValue, CONTENT_TYPE, COOKIE, SET_COOKIE},
+        Method, StatusCode, Url,
+    },
+    serde::{de::DeserializeOwned, Deserialize},
+    thiserror::Error,
+    tokio::sync::Mutex,
+};
+
+mod builder;
+mod coalesce;
+mod session;
+
+use {coalesce::Coalescer, session::SessionCookies};
+
+pub use {
+    builder::IracingApiClientBuilder,
+    session::{SavedCookie, SavedSession, SessionFileError},
+};
+
+const BASE_URL: &str = "https://members-ng.iracing.com";
 
 #[derive(Deserialize, Debug, Clone)]
 struct LinkResponseBody {
-    link: String,
+    link: Url,
+}
+
+/// Supplies the credentials used to log in to iRacing
+///
+/// The provider is queried on the initial login, and again whenever the
+/// session cookie expires and the client needs to log in again. Implemented
+/// for [AuthRequestBody] and for closures returning one. The returned password
+/// must already be hashed, see [AuthRequestBody::new].
+pub trait CredentialProvider: Send + Sync {
+    fn credentials(&self) -> AuthRequestBody;
+}
+
+impl CredentialProvider for AuthRequestBody {
+    fn credentials(&self) -> AuthRequestBody {
+        self.clone()
+    }
 }
 
+impl<F> CredentialProvider for F
+where
+    F: Fn() -> AuthRequestBody + Send + Sync,
+{
+    fn credentials(&self) -> AuthRequestBody {
+        self()
+    }
+}
+
+type LoginHook = Box<dyn Fn(&AuthSuccessBody) + Send + Sync>;
+
 /// An authenticated iRacing API client
+///
+/// When a `/data` endpoint reports that the session has expired, the client
+/// logs in again with its [CredentialProvider] and retries the request once.
 pub struct IracingApiClient {
-    reqwest: Client,
-    /// The data returned by iRacing after authentication
-    pub auth: AuthSuccessBody,
+    http: Http,
+    /// `None` if the client was restored from a [SavedSession] without
+    /// credentials, in which case it cannot log in again
+    credentials: Option<Box<dyn CredentialProvider>>,
+    /// The data returned by iRacing after the most recent authentication
+    auth: RwLock<AuthSuccessBody>,
+    /// Incremented on every successful re-login, so that concurrent requests
+    /// failing with the same expired cookie only trigger one login
+    auth_generation: AtomicU64,
+    login_lock: Mutex<()>,
+    on_relogin: Option<LoginHook>,
+    rate_limiter: RateLimiter,
+    /// Applied to requests to `/data` endpoints
+    retry_policy: RetryPolicy,
+    /// Applied to fetches of the links returned by `/data` endpoints
+    link_retry_policy: RetryPolicy,
+    /// The number of chunks downloaded at once
+    chunk_concurrency: usize,
+    cache: Option<ResponseCache>,
+    coalescer: Coalescer,
 }
 
 impl IracingApiClient {
@@ -28,33 +111,724 @@ impl IracingApiClient {
     /// - iRacing decides to require manual login verification for the user
     ///
     /// See the documentation of [AuthError] for more details
+    pub async fn new(email: &str, password: &str) -> Result<Self, ClientInitError> {
+        Self::builder().credentials(email, password).build().await
+    }
+
+    /// Create a new iRacing API client, authenticating with a password already
+    /// hashed with [hash_password]
     ///
-    /// # Panics
+    /// # Errors
     ///
-    /// Panics if the HTTP response from iRacing is malformed
-    pub async fn new(email: &str, password: &str) -> Result<Self, ClientInitError> {
-        // Initialize a reqwest client with a cookie store enabled
-        let reqwest = Client::builder()
-            .cookie_store(true)
-            .build()
-            .map_err(ClientInitError::ReqwestInitError)?;
-
-        // Attempt to authenticate with iRacing
-        let auth_response = reqwest
-            .post("https://members-ng.iracing.com/auth")
-            .json(&AuthRequestBody {
-                email: email.to_string(),
-                password: password.to_string(),
+    /// See [IracingApiClient::new]
+    pub async fn with_hashed_password(
+        email: &str,
+        hashed_password: &str,
+    ) -> Result<Self, ClientInitError> {
+        Self::builder()
+            .hashed_credentials(email, hashed_password)
+            .build()
+            .await
+    }
+
+    /// Start configuring a client, e.g. to change the base URL, timeouts or
+    /// retry behavior
+    pub fn builder() -> IracingApiClientBuilder {
+        IracingApiClientBuilder::default()
+    }
+
+    /// The request budget reported by the most recent `/data` response, if
+    /// any request has been made yet
+    ///
+    /// `remaining` also accounts for requests sent since that response.
+    pub fn rate_limit(&self) -> Option<RateLimit> {
+        self.rate_limiter.current()
+    }
+
+    /// The data returned by iRacing after the most recent authentication
+    pub fn auth(&self) -> AuthSuccessBody {
+        self.auth.read().unwrap().clone()
+    }
+
+    /// Save the current session, so that it can be restored with
+    /// [IracingApiClientBuilder::session] without logging in again
+    pub fn save_session(&self) -> SavedSession {
+        SavedSession {
+            auth: self.auth(),
+            cookies: self.http.cookies.saved(),
+        }
+    }
+
+    /// Log in again, unless another task already did so since `generation`
+    /// was observed
+    async fn relogin(&self, generation: u64) -> Result<(), ClientInitError> {
+        let _guard = self.login_lock.lock().await;
+        if self.auth_generation.load(Ordering::SeqCst) != generation {
+            return Ok(());
+        }
+
+        let credentials = self
+            .credentials
+            .as_ref()
+            .ok_or(ClientInitError::MissingCredentials)?;
+        let auth = self.http.login(&credentials.credentials()).await?;
+        if let Some(hook) = &self.on_relogin {
+            hook(&auth);
+        }
+        *self.auth.write().unwrap() = auth;
+        self.auth_generation.fetch_add(1, Ordering::SeqCst);
+
+        Ok(())
+    }
+
+    /// Request an endpoint, and deserialize the data it returns
+    pub async fn request<E: Endpoint>(&self, endpoint: &E) -> Result<E::Response, ApiError> {
+        endpoint
+            .validate()
+            .map_err(|source| ApiError::InvalidQuery {
+                endpoint: E::PATH.to_string(),
+                source,
+            })?;
+        let body = self
+            .fetch_body(
+                E::PATH,
+                &endpoint.query(),
+                E::ENVELOPE,
+                endpoint.cache_ttl(),
+            )
+            .await?;
+        endpoint.decode(&body)
+    }
+
+    /// Request a chunked endpoint, and download the rows of every chunk it
+    /// describes
+    pub async fn request_chunked<E: ChunkedEndpoint>(
+        &self,
+        endpoint: &E,
+    ) -> Result<Chunked<E::Response, E::Row>, ApiError> {
+        let response = self.request(endpoint).await?;
+        let rows = match E::chunk_info(&response) {
+            Some(chunk_info) => self.fetch_chunks(E::PATH, chunk_info).await?,
+            None => Vec::new(),
+        };
+        Ok(Chunked { response, rows })
+    }
+
+    /// Request a chunked endpoint, and lazily download the rows of every
+    /// chunk it describes
+    pub async fn request_stream<'a, E: ChunkedEndpoint>(
+        &'a self,
+        endpoint: &E,
+    ) -> Result<
+        (
+            E::Response,
+            impl Stream<Item = Result<E::Row, ApiError>> + 'a,
+        ),
+        ApiError,
+    >
+    where
+        E::Row: 'a,
+    {
+        let response = self.request(endpoint).await?;
+        let urls = E::chunk_info(&response)
+            .map(|chunk_info| chunk_info.chunk_urls().collect())
+            .unwrap_or_default();
+        Ok((response, self.row_stream(E::PATH, urls)))
+    }
+
+    pub async fn session_results(
+        &self,
+        subsession_id: u32,
+        include_licenses: bool,
+    ) -> Result<results::SessionResult, ApiError> {
+        self.request(
+            &results::SessionResultsQuery::new(subsession_id).include_licenses(include_licenses),
+        )
+        .await
+    }
+
+    /// Returns every lap of a driver or team in a session
+    ///
+    /// `simsession_number` is `0` for the main event, `-1` for the preceding
+    /// session, and so on.
+    pub async fn lap_data(
+        &self,
+        subsession_id: u32,
+        simsession_number: i32,
+        entrant: lap_data::Entrant,
+    ) -> Result<Chunked<lap_data::LapData, lap_data::Lap>, ApiError> {
+        self.request_chunked(&lap_data::LapDataQuery::new(
+            subsession_id,
+            simsession_number,
+            entrant,
+        ))
+        .await
+    }
+
+    /// Returns every lap of every driver in a session, e.g. to pivot with
+    /// [positions_by_driver](lap_chart::positions_by_driver) or
+    /// [running_order](lap_chart::running_order)
+    pub async fn lap_chart_data(
+        &self,
+        subsession_id: u32,
+        simsession_number: i32,
+    ) -> Result<Chunked<lap_chart::LapChartData, lap_data::Lap>, ApiError> {
+        self.request_chunked(&lap_chart::LapChartQuery::new(
+            subsession_id,
+            simsession_number,
+        ))
+        .await
+    }
+
+    /// Returns the events of a session, e.g. cautions, incidents and pit stops
+    pub async fn event_log(
+        &self,
+        subsession_id: u32,
+        simsession_number: i32,
+    ) -> Result<Chunked<event_log::EventLog, event_log::EventLogEntry>, ApiError> {
+        self.request_chunked(&event_log::EventLogQuery::new(
+            subsession_id,
+            simsession_number,
+        ))
+        .await
+    }
+
+    /// Search official series results, yielding one row per driver and
+    /// session as the chunks are downloaded
+    ///
+    /// The query is checked against iRacing's rules before it is sent, see
+    /// [SeriesSearchQuery](search::SeriesSearchQuery).
+    pub async fn search_series<'a>(
+        &'a self,
+        query: &search::SeriesSearchQuery,
+    ) -> Result<impl Stream<Item = Result<search::SeriesSearchResult, ApiError>> + 'a, ApiError>
+    {
+        Ok(self.request_stream(query).await?.1)
+    }
+
+    /// Search hosted and league sessions, yielding one row per session as the
+    /// chunks are downloaded
+    ///
+    /// The query is checked against iRacing's rules before it is sent, see
+    /// [HostedSearchQuery](search::HostedSearchQuery).
+    pub async fn search_hosted<'a>(
+        &'a self,
+        query: &search::HostedSearchQuery,
+    ) -> Result<impl Stream<Item = Result<search::HostedSearchResult, ApiError>> + 'a, ApiError>
+    {
+        Ok(self.request_stream(query).await?.1)
+    }
+
+    pub async fn season_results(
+        &self,
+        query: season_results::SeasonResultsQuery,
+    ) -> Result<season_results::SeasonResults, ApiError> {
+        self.request(&query).await
+    }
+
+    pub async fn all_cars(&self) -> Result<Vec<car::Car>, ApiError> {
+        self.request(&car::CarsQuery).await
+    }
+
+    pub async fn all_car_classes(&self) -> Result<Vec<car_class::CarClass>, ApiError> {
+        self.request(&car_class::CarClassesQuery).await
+    }
+
+    /// Returns a map of car IDs to asset data, like the actual API
+    pub async fn all_car_assets(&self) -> Result<HashMap<u32, car::CarAssets>, ApiError> {
+        self.request(&car::CarAssetsQuery).await
+    }
+
+    /// Returns every track configuration
+    pub async fn all_tracks(&self) -> Result<Vec<track::Track>, ApiError> {
+        self.request(&track::TracksQuery).await
+    }
+
+    /// Returns a map of track IDs to asset data, like the actual API
+    pub async fn all_track_assets(&self) -> Result<HashMap<u32, track::TrackAssets>, ApiError> {
+        self.request(&track::TrackAssetsQuery).await
+    }
+
+    pub async fn series(&self) -> Result<Vec<series::Series>, ApiError> {
+        self.request(&series::SeriesQuery).await
+    }
+
+    /// Returns the current seasons of every series, with their weekly
+    /// schedules
+    pub async fn series_seasons(
+        &self,
+        include_series: bool,
+    ) -> Result<Vec<series::Season>, ApiError> {
+        self.request(&series::SeriesSeasonsQuery::new().include_series(include_series))
+            .await
+    }
+
+    /// Returns a series, with every one of its seasons
+    pub async fn series_past_seasons(
+        &self,
+        series_id: u32,
+    ) -> Result<series::PastSeries, ApiError> {
+        Ok(self
+            .request(&series::PastSeasonsQuery::new(series_id))
+            .await?
+            .series)
+    }
+
+    /// Returns a map of series IDs to asset data, like the actual API
+    pub async fn series_assets(&self) -> Result<HashMap<u32, series::SeriesAssets>, ApiError> {
+        self.request(&series::SeriesAssetsQuery).await
+    }
+
+    /// Returns the sessions starting within a few hours of `from`, or of now
+    /// if `from` is `None`
+    pub async fn season_race_guide(
+        &self,
+        from: Option<DateTime<Utc>>,
+        include_end_after_from: bool,
+    ) -> Result<season::RaceGuide, ApiError> {
+        let mut query =
+            season::RaceGuideQuery::new().include_end_after_from(include_end_after_from);
+        if let Some(from) = from {
+            query = query.from(from);
+        }
+        self.request(&query).await
+    }
+
+    /// Returns the start times of the next `count` sessions of a series
+    ///
+    /// The race guide only covers the next few hours, so later start times are
+    /// computed from the schedules of the series' current seasons. Fewer start
+    /// times are only returned if those seasons end first.
+    pub async fn next_race_starts(
+        &self,
+        series_id: u32,
+        count: usize,
+    ) -> Result<Vec<DateTime<Utc>>, ApiError> {
+        let guide = self.season_race_guide(None, false).await?;
+        let mut starts: BTreeSet<_> = guide
+            .upcoming(series_id)
+            .map(|session| session.start_time)
+            .take(count)
+            .collect();
+
+        if starts.len() < count {
+            let after = starts.iter().copied().fold(Utc::now(), DateTime::max);
+            let seasons = self.request(&series::SeriesSeasonsQuery::new()).await?;
+            let scheduled = seasons
+                .iter()
+                .filter(|season| season.series_id == series_id)
+                .flat_map(series::Season::start_times)
+                .filter(|start_time| *start_time > after);
+            starts.extend(scheduled);
+        }
+        Ok(starts.into_iter().take(count).collect())
+    }
+
+    /// Returns the account of the logged in member
+    pub async fn member_info(&self) -> Result<member::MemberInfo, ApiError> {
+        self.request(&member::MemberInfoQuery).await
+    }
+
+    /// Look up several members at once. Licenses are only included if
+    /// `include_licenses` is set.
+    pub async fn members(
+        &self,
+        cust_ids: &[u32],
+        include_licenses: bool,
+    ) -> Result<Vec<member::Member>, ApiError> {
+        let query =
+            member::MembersQuery::new(cust_ids.iter().copied()).include_licenses(include_licenses);
+        Ok(self.request(&query).await?.members)
+    }
+
+    /// Returns the public profile of a member, including their recent events
+    pub async fn member_profile(&self, cust_id: u32) -> Result<member::MemberProfile, ApiError> {
+        self.request(&member::MemberProfileQuery::new(cust_id))
+            .await
+    }
+
+    /// Returns the career statistics of a member per category, or of the
+    /// logged in member if `cust_id` is `None`
+    pub async fn member_career(
+        &self,
+        cust_id: Option<u32>,
+    ) -> Result<stats::MemberCareer, ApiError> {
+        self.request(&stats::MemberCareerQuery::new(self.cust_id_or_own(cust_id)))
+            .await
+    }
+
+    /// Returns the statistics of a member per year and category, or of the
+    /// logged in member if `cust_id` is `None`
+    pub async fn member_yearly(
+        &self,
+        cust_id: Option<u32>,
+    ) -> Result<stats::MemberYearly, ApiError> {
+        self.request(&stats::MemberYearlyQuery::new(self.cust_id_or_own(cust_id)))
+            .await
+    }
+
+    /// Returns the number of sessions and wins of a member this year, or of
+    /// the logged in member if `cust_id` is `None`
+    pub async fn member_summary(
+        &self,
+        cust_id: Option<u32>,
+    ) -> Result<stats::MemberSummary, ApiError> {
+        self.request(&stats::MemberSummaryQuery::new(
+            self.cust_id_or_own(cust_id),
+        ))
+        .await
+    }
+
+    /// Returns a recap of a member's year, or of one season of the year, for
+    /// the logged in member if `cust_id` is `None`. `year` defaults to the
+    /// current year.
+    pub async fn member_recap(
+        &self,
+        cust_id: Option<u32>,
+        year: Option<u32>,
+        season: Option<u32>,
+    ) -> Result<stats::MemberRecap, ApiError> {
+        let mut query = stats::MemberRecapQuery::new(self.cust_id_or_own(cust_id));
+        if let Some(year) = year {
+            query = query.year(year);
+        }
+        if let Some(season) = season {
+            query = query.season(season);
+        }
+        self.request(&query).await
+    }
+
+    fn cust_id_or_own(&self, cust_id: Option<u32>) -> u32 {
+        cust_id.unwrap_or_else(|| self.auth.read().unwrap().cust_id)
+    }
+
+    /// Find the drivers whose name matches `search_term`, optionally only
+    /// among the members of a league
+    pub async fn lookup_drivers(
+        &self,
+        search_term: &str,
+        league_id: Option<u32>,
+    ) -> Result<Vec<lookup::DriverMatch>, ApiError> {
+        let mut query = lookup::DriversQuery::new(search_term);
+        if let Some(league_id) = league_id {
+            query = query.league_id(league_id);
+        }
+        self.request(&query).await
+    }
+
+    /// Download every chunk described by `chunk_info`, and concatenate their
+    /// rows in order
+    ///
+    /// Up to [chunk_concurrency](IracingApiClientBuilder::chunk_concurrency)
+    /// chunks are downloaded at once. `endpoint` is the endpoint that returned
+    /// `chunk_info`, used in errors.
+    pub async fn fetch_chunks<T: DeserializeOwned>(
+        &self,
+        endpoint: &str,
+        chunk_info: &ChunkInfo,
+    ) -> Result<Vec<T>, ApiError> {
+        self.chunk_stream(endpoint, chunk_info).try_collect().await
+    }
+
+    /// Lazily download the chunks described by `chunk_info`, yielding their
+    /// rows in order
+    ///
+    /// Up to [chunk_concurrency](IracingApiClientBuilder::chunk_concurrency)
+    /// chunks are downloaded ahead of the rows being consumed. `endpoint` is
+    /// the endpoint that returned `chunk_info`, used in errors.
+    pub fn chunk_stream<'a, T: DeserializeOwned + 'a>(
+        &'a self,
+        endpoint: &'a str,
+        chunk_info: &ChunkInfo,
+    ) -> impl Stream<Item = Result<T, ApiError>> + 'a {
+        self.row_stream(endpoint, chunk_info.chunk_urls().collect())
+    }
+
+    fn row_stream<'a, T: DeserializeOwned + 'a>(
+        &'a self,
+        endpoint: &'a str,
+        urls: Vec<String>,
+    ) -> impl Stream<Item = Result<T, ApiError>> + 'a {
+        stream::iter(urls)
+            .map(move |url| async move {
+                let url = Url::parse(&url).map_err(|err| ApiError::LinkFetchFailure {
+                    endpoint: endpoint.to_string(),
+                    status: None,
+                    source: Some(TransportError::new(TransportErrorKind::Other, err)),
+                })?;
+                let body = self.fetch_link(endpoint, url).await?;
+                decode::<Vec<T>>(endpoint, &body)
+            })
+            .buffered(self.chunk_concurrency)
+            .flat_map(|chunk| match chunk {
+                Ok(rows) => stream::iter(rows).map(Ok).left_stream(),
+                Err(err) => stream::once(future::ready(Err(err))).right_stream(),
             })
-            .send()
+    }
+
+    /// Fetch the body of the data returned by `endpoint`, sharing the result
+    /// with identical requests made at the same time
+    async fn fetch_body(
+        &self,
+        endpoint: &str,
+        query: &[(&str, String)],
+        envelope: Envelope,
+        ttl: Option<CacheTtl>,
+    ) -> Result<Vec<u8>, ApiError> {
+        let key = self.http.url(endpoint, query).to_string();
+        self.coalescer
+            .run(key, || self.fetch_cached(endpoint, query, envelope, ttl))
             .await
-            .map_err(ClientInitError::ConnectionFailure)?
-            .json::<serde_json::Value>()
+    }
+
+    /// Fetch the body of the data returned by `endpoint`, from the cache if
+    /// possible
+    async fn fetch_cached(
+        &self,
+        endpoint: &str,
+        query: &[(&str, String)],
+        envelope: Envelope,
+        ttl: Option<CacheTtl>,
+    ) -> Result<Vec<u8>, ApiError> {
+        let cache = match &self.cache {
+            Some(cache) => cache,
+            None => return self.fetch_uncached(endpoint, query, envelope).await,
+        };
+        let cache_failure = |source| ApiError::CacheFailure {
+            endpoint: endpoint.to_string(),
+            source: Arc::new(source),
+        };
+
+        let url = self.http.url(endpoint, query);
+        let ttl = cache.ttl_for(endpoint, ttl);
+        match (cache.current_mode(), ttl) {
+            (CacheMode::CacheOnly, _) => {
+                return cache
+                    .get(&url, None)
+                    .map_err(cache_failure)?
+                    .ok_or_else(|| ApiError::CacheMiss {
+                        endpoint: endpoint.to_string(),
+                    });
+            }
+            (CacheMode::ReadWrite, Some(ttl)) => {
+                if let Some(body) = cache.get(&url, Some(ttl)).map_err(cache_failure)? {
+                    return Ok(body);
+                }
+            }
+            _ => {}
+        }
+
+        let body = self.fetch_uncached(endpoint, query, envelope).await?;
+        if ttl.is_some() {
+            // The data was fetched successfully, failing to cache it (e.g.
+            // because the disk is full) only means it is fetched again
+            let _ = cache.put(&url, &body);
+        }
+        Ok(body)
+    }
+
+    /// Request `endpoint`, and follow the link it returns if the data is
+    /// link-wrapped
+    async fn fetch_uncached(
+        &self,
+        endpoint: &str,
+        query: &[(&str, String)],
+        envelope: Envelope,
+    ) -> Result<Vec<u8>, ApiError> {
+        let body = self.request_data(endpoint, query).await?;
+        match envelope {
+            Envelope::Direct => Ok(body),
+            Envelope::Link => {
+                let link: LinkResponseBody = decode(endpoint, &body)?;
+                self.fetch_link(endpoint, link.link).await
+            }
+        }
+    }
+
+    /// Request a `/data` endpoint, retrying according to the retry policy, and
+    /// logging in again and retrying once if the session has expired
+    async fn request_data(
+        &self,
+        endpoint: &str,
+        query: &[(&str, String)],
+    ) -> Result<Vec<u8>, ApiError> {
+        let connection_failure = |source| ApiError::ConnectionFailure {
+            endpoint: endpoint.to_string(),
+            source,
+        };
+
+        let mut attempt = 1;
+        let mut relogged_in = false;
+        loop {
+            let generation = self.auth_generation.load(Ordering::SeqCst);
+            self.rate_limiter.acquire().await;
+            let result = self.http.send(Method::GET, endpoint, query, None).await;
+            let response = match result {
+                Ok(response) => response,
+                Err(err) if self.retry_policy.retries_error(&err) => {
+                    self.retry_policy
+                        .wait_to_retry(&mut attempt)
+                        .await
+                        .ok_or_else(|| connection_failure(err))?;
+                    continue;
+                }
+                Err(err) => return Err(connection_failure(err)),
+            };
+            self.rate_limiter.update(&response.headers);
+
+            let status = response.status;
+            if status == StatusCode::UNAUTHORIZED && !relogged_in {
+                self.relogin(generation).await.map_err(|source| {
+                    ApiError::ReauthenticationFailure {
+                        endpoint: endpoint.to_string(),
+                        source: Arc::new(source),
+                    }
+                })?;
+                relogged_in = true;
+                continue;
+            }
+            if self.retry_policy.retries_status(status)
+                && self
+                    .retry_policy
+                    .wait_to_retry(&mut attempt)
+                    .await
+                    .is_some()
+            {
+                continue;
+            }
+            check_status(endpoint, status)?;
+
+            return Ok(response.body);
+        }
+    }
+
+    /// Fetch data linked by `endpoint`, retrying according to the link retry
+    /// policy
+    async fn fetch_link(&self, endpoint: &str, link: Url) -> Result<Vec<u8>, ApiError> {
+        let link_fetch_failure = |status, source| ApiError::LinkFetchFailure {
+            endpoint: endpoint.to_string(),
+            status,
+            source,
+        };
+        let policy = &self.link_retry_policy;
+        let mut attempt = 1;
+        loop {
+            let response = match self.http.fetch(link.clone()).await {
+                Ok(response) => response,
+                Err(err) if policy.retries_error(&err) => {
+                    policy
+                        .wait_to_retry(&mut attempt)
+                        .await
+                        .ok_or_else(|| link_fetch_failure(None, Some(err)))?;
+                    continue;
+                }
+                Err(err) => return Err(link_fetch_failure(None, Some(err))),
+            };
+
+            let status = response.status;
+            if !status.is_success() {
+                if policy.retries_status(status)
+                    && policy.wait_to_retry(&mut attempt).await.is_some()
+                {
+                    continue;
+                }
+                return Err(link_fetch_failure(Some(status), None));
+            }
+
+            return Ok(response.body);
+        }
+    }
+}
+
+/// The [Transport] used to reach iRacing, and the cookies of the current
+/// session
+struct Http {
+    transport: Box<dyn Transport>,
+    base_url: Url,
+    cookies: SessionCookies,
+}
+
+impl Http {
+    /// The URL of `path` (e.g. `/data/car/get`) relative to the base URL
+    fn url(&self, path: &str, query: &[(&str, String)]) -> Url {
+        let mut url = self.base_url.clone();
+        url.set_path(&format!(
+            "{}{}",
+            self.base_url.path().trim_end_matches('/'),
+            path
+        ));
+        if !query.is_empty() {
+            url.query_pairs_mut().extend_pairs(query);
+        }
+        url
+    }
+
+    /// Send a request to `path` with the session cookies attached, and store
+    /// the cookies set by the response
+    async fn send(
+        &self,
+        method: Method,
+        path: &str,
+        query: &[(&str, String)],
+        json_body: Option<Vec<u8>>,
+    ) -> Result<Response, TransportError> {
+        let mut request = Request::new(method, self.url(path, query));
+        if let Some(cookies) = self.cookies.header(&self.base_url) {
+            request.headers.insert(COOKIE, cookies);
+        }
+        if json_body.is_some() {
+            request
+                .headers
+                .insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+        }
+        request.body = json_body;
+
+        let response = self.transport.send(request).await?;
+        self.cookies
+            .store(response.headers.get_all(SET_COOKIE).iter(), &self.base_url);
+        Ok(response)
+    }
+
+    /// Fetch a link returned by a `/data` endpoint. Links are presigned, so
+    /// no cookies are sent.
+    async fn fetch(&self, link: Url) -> Result<Response, TransportError> {
+        self.transport.send(Request::new(Method::GET, link)).await
+    }
+
+    /// Restore the cookies of a saved session
+    fn restore_cookies(&self, session: &SavedSession) {
+        self.cookies.restore(&session.cookies, &self.base_url);
+    }
+
+    /// Check whether the current session cookies are accepted by iRacing.
+    /// Anything but a successful response, e.g. a redirect to the login page,
+    /// means they are not.
+    async fn session_is_valid(&self, rate_limiter: &RateLimiter) -> Result<bool, TransportError> {
+        rate_limiter.acquire().await;
+        let response = self
+            .send(Method::GET, "/data/member/info", &[], None)
+            .await?;
+        rate_limiter.update(&response.headers);
+        Ok(response.status.is_success())
+    }
+
+    /// Authenticate with iRacing, storing the session cookies
+    async fn login(
+        &self,
+        credentials: &AuthRequestBody,
+    ) -> Result<AuthSuccessBody, ClientInitError> {
+        let body = serde_json::to_vec(credentials).expect("Credentials are always valid JSON");
+        let response = self
+            .send(Method::POST, "/auth", &[], Some(body))
             .await
             .map_err(ClientInitError::ConnectionFailure)?;
+        let auth_response = serde_json::from_slice(&response.body)
+            .map_err(|err| ClientInitError::InvalidAuthResponse(AuthResponseError::NotJson(err)))?;
 
-        match AuthResponse::from_json(auth_response) {
-            AuthResponse::Success(auth) => Ok(IracingApiClient { reqwest, auth }),
+        match AuthResponse::from_json(auth_response)
+            .map_err(ClientInitError::InvalidAuthResponse)?
+        {
+            AuthResponse::Success(auth) => Ok(auth),
             AuthResponse::Failure(body) => {
                 // Match on the error message returned from iRacing
                 let err_kind = match body.message.as_str() {
@@ -75,99 +849,1107 @@ impl IracingApiClient {
             }
         }
     }
+}
 
-    pub async fn session_results(
-        &self,
-        subsession_id: u32,
-        include_licenses: bool,
-    ) -> Result<results::SessionResult> {
-        let response: LinkResponseBody = self
-            .reqwest
-            .get("https://members-ng.iracing.com/data/results/get")
-            .query(&[
-                ("subsession_id", subsession_id.to_string()),
-                ("include_licenses", include_licenses.to_string()),
-            ])
-            .send()
-            .await?
-            .json()
-            .await?;
+/// Map an HTTP status returned by a `/data` endpoint to an [ApiError]
+fn check_status(endpoint: &str, status: StatusCode) -> Result<(), ApiError> {
+    let endpoint = endpoint.to_string();
+    match status {
+        status if status.is_success() => Ok(()),
+        StatusCode::UNAUTHORIZED => Err(ApiError::Unauthorized { endpoint }),
+        StatusCode::NOT_FOUND => Err(ApiError::NotFound { endpoint }),
+        StatusCode::TOO_MANY_REQUESTS => Err(ApiError::RateLimited { endpoint }),
+        status => Err(ApiError::UnexpectedStatus { endpoint, status }),
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum ClientInitError {
+    #[error("No credentials were provided")]
+    MissingCredentials,
+    #[error("The restored session has expired, and no credentials were provided")]
+    SessionExpired,
+    #[error("Invalid base URL")]
+    InvalidBaseUrl(#[source] url::ParseError),
+    #[error("Cannot initialize HTTP client")]
+    ReqwestInitError(reqwest::Error),
+    #[error("Connection failure")]
+    ConnectionFailure(TransportError),
+    #[error("Authentication with iRacing failed")]
+    AuthenticationFailure(#[from] AuthError),
+    #[error("Unexpected response from the authentication endpoint")]
+    InvalidAuthResponse(#[source] AuthResponseError),
+}
+
+/// The ways a request to one of iRacing's `/data` endpoints can fail
+#[derive(Error, Debug, Clone)]
+pub enum ApiError {
+    /// The request could not be sent, or the response could not be read
+    #[error("Connection failure while requesting {endpoint}")]
+    ConnectionFailure {
+        endpoint: String,
+        #[source]
+        source: TransportError,
+    },
+
+    /// The session cookie is missing or has expired, and logging in again
+    /// did not help
+    #[error("Unauthorized to request {endpoint}")]
+    Unauthorized { endpoint: String },
+
+    /// The session expired, and logging in again failed
+    #[error("Could not log in again while requesting {endpoint}")]
+    ReauthenticationFailure {
+        endpoint: String,
+        #[source]
+        source: Arc<ClientInitError>,
+    },
+
+    /// The requested resource (e.g. a subsession) does not exist
+    #[error("{endpoint} was not found")]
+    NotFound { endpoint: String },
+
+    /// iRacing is throttling requests, try again later
+    #[error("Rate limited while requesting {endpoint}")]
+    RateLimited { endpoint: String },
+
+    /// The endpoint returned an HTTP status not covered by another variant
+    #[error("{endpoint} returned unexpected HTTP status {status}")]
+    UnexpectedStatus {
+        endpoint: String,
+        status: StatusCode,
+    },
+
+    /// The endpoint responded, but the data behind the link it returned
+    /// could not be fetched
+    #[error("Could not fetch the data linked by {endpoint}")]
+    LinkFetchFailure {
+        endpoint: String,
+        /// The HTTP status of the link response, if one was received
+        status: Option<StatusCode>,
+        #[source]
+        source: Option<TransportError>,
+    },
+
+    /// The response did not match the model. This should be considered a bug.
+    #[error("Response from {endpoint} did not match the model at `{path}`")]
+    Deserialization {
+        endpoint: String,
+        /// The serde path of the value that failed to deserialize, e.g.
+        /// `session_results[0].results[3].helmet`
+        path: String,
+        #[source]
+        source: Arc<serde_json::Error>,
+    },
+
+    /// One entry of an asset map (e.g. from `/data/car/assets`) could not be
+    /// decoded. This should be considered a bug.
+    #[error("Invalid assets for id `{id}` from {endpoint}")]
+    InvalidAssets {
+        endpoint: String,
+        /// The key of the offending entry, e.g. the car ID
+        id: String,
+        /// The serde path within the entry that failed to deserialize, or
+        /// `None` if the key itself is not a valid ID
+        path: Option<String>,
+        #[source]
+        source: Option<Arc<serde_json::Error>>,
+    },
+
+    /// The request was not sent, because iRacing would reject it
+    #[error("Invalid request to {endpoint}")]
+    InvalidQuery {
+        endpoint: String,
+        #[source]
+        source: QueryError,
+    },
+
+    /// The client only serves cached responses, and the response is not in
+    /// the cache
+    #[error("{endpoint} is not cached")]
+    CacheMiss { endpoint: String },
 
-        let data = self.reqwest.get(response.link).send().await?.json().await?;
+    /// The cache directory could not be read. Failing to write to it does not
+    /// fail a request.
+    #[error("Could not access the cached response of {endpoint}")]
+    CacheFailure {
+        endpoint: String,
+        #[source]
+        source: Arc<std::io::Error>,
+    },
+}
 
-        Ok(data)
+impl ApiError {
+    /// The path of the endpoint that failed, e.g. `/data/results/get`
+    pub fn endpoint(&self) -> &str {
+        match self {
+            ApiError::ConnectionFailure { endpoint, .. }
+            | ApiError::Unauthorized { endpoint }
+            | ApiError::ReauthenticationFailure { endpoint, .. }
+            | ApiError::NotFound { endpoint }
+            | ApiError::RateLimited { endpoint }
+            | ApiError::UnexpectedStatus { endpoint, .. }
+            | ApiError::LinkFetchFailure { endpoint, .. }
+            | ApiError::Deserialization { endpoint, .. }
+            | ApiError::InvalidAssets { endpoint, .. }
+            | ApiError::InvalidQuery { endpoint, .. }
+            | ApiError::CacheMiss { endpoint }
+            | ApiError::CacheFailure { endpoint, .. } => endpoint,
+        }
     }
 
-    pub async fn season_results(
-        &self,
-        query: season_results::SeasonResultsQuery,
-    ) -> Result<season_results::SeasonResults> {
-        let response: LinkResponseBody = self
-            .reqwest
-            .get("https://members-ng.iracing.com/data/results/season_results")
-            .query(&query.as_query_params())
-            .send()
-            .await?
-            .json()
-            .await?;
+    /// The HTTP status that caused the failure, if there was one
+    pub fn status(&self) -> Option<StatusCode> {
+        match self {
+            ApiError::ConnectionFailure { .. } => None,
+            ApiError::Unauthorized { .. } => Some(StatusCode::UNAUTHORIZED),
+            ApiError::ReauthenticationFailure { .. } => None,
+            ApiError::NotFound { .. } => Some(StatusCode::NOT_FOUND),
+            ApiError::RateLimited { .. } => Some(StatusCode::TOO_MANY_REQUESTS),
+            ApiError::UnexpectedStatus { status, .. } => Some(*status),
+            ApiError::LinkFetchFailure { status, .. } => *status,
+            ApiError::Deserialization { .. } | ApiError::InvalidAssets { .. } => None,
+            ApiError::InvalidQuery { .. } => None,
+            ApiError::CacheMiss { .. } | ApiError::CacheFailure { .. } => None,
+        }
+    }
+}
 
-        let data = self.reqwest.get(response.link).send().await?.json().await?;
+#[cfg(test)]
+mod test {
+    use super::{
+        check_status, ApiError, ClientInitError, Http, IracingApiClient, SavedCookie, SavedSession,
+        SessionCookies,
+    };
+    use crate::{
+        cache::{CacheMode, ResponseCache},
+        endpoint::{ChunkedEndpoint, Endpoint, QueryError},
+        model::{
+            chunk_info::ChunkInfo,
+            lap_data::Entrant,
+            search::SeriesSearchQuery,
+            season_results::{EventType, SeasonResultsQuery},
+        },
+        retry::RetryPolicy,
+        transport::{MemoryTransport, Response},
+    };
+    use chrono::{DateTime, Utc};
+    use futures::{StreamExt, TryStreamExt};
+    use reqwest::{Method, StatusCode, Url};
+    use std::{
+        sync::{
+            atomic::{AtomicU32, Ordering},
+            Arc,
+        },
+        time::Duration,
+    };
 
-        Ok(data)
+    fn transport() -> Arc<MemoryTransport> {
+        let transport = Arc::new(MemoryTransport::new());
+        transport
+            .login(&serde_json::from_str(include_str!("../test_files/auth/success.json")).unwrap());
+        transport
     }
 
-    pub async fn all_cars(&self) -> Result<Vec<car::Car>> {
-        let response: LinkResponseBody = self
-            .reqwest
-            .get("https://members-ng.iracing.com/data/car/get")
-            .send()
-            .await?
-            .json()
-            .await?;
+    async fn client(transport: &Arc<MemoryTransport>) -> IracingApiClient {
+        IracingApiClient::builder()
+            .credentials("email@example.com", "password")
+            .transport(transport.clone())
+            .retry_policy(RetryPolicy::default().initial_backoff(Duration::from_millis(1)))
+            .build()
+            .await
+            .unwrap()
+    }
+
+    fn paths(transport: &MemoryTransport) -> Vec<String> {
+        transport
+            .requests()
+            .iter()
+            .map(|request| request.url.path().to_string())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn client_follows_link() {
+        let transport = transport();
+        transport.link(
+            "/data/carclass/get",
+            &serde_json::from_str(include_str!("../test_files/car_class/get.json")).unwrap(),
+        );
 
-        let data = self.reqwest.get(response.link).send().await?.json().await?;
+        let client = client(&transport).await;
+        assert_eq!(client.auth().cust_id, 123456);
+        let classes = client.all_car_classes().await.unwrap();
+        assert_eq!(classes.len(), 2);
+        assert_eq!(
+            paths(&transport),
+            ["/auth", "/data/carclass/get", "/links/0"]
+        );
 
-        Ok(data)
+        let login = &transport.requests()[0];
+        let credentials: serde_json::Value =
+            serde_json::from_slice(login.body.as_ref().unwrap()).unwrap();
+        assert_eq!(
+            credentials["password"],
+            "D0OVE01PFmrT2kHcvwRzTNY3wFbYHnbTD3DoJi3OcSI="
+        );
     }
 
-    /// Returns a map of car IDs to asset data, like the actual API
-    pub async fn all_car_assets(&self) -> Result<HashMap<u32, car::CarAssets>> {
-        let response: LinkResponseBody = self
-            .reqwest
-            .get("https://members-ng.iracing.com/data/car/assets")
-            .send()
-            .await?
-            .json()
-            .await?;
+    async fn cached_client(
+        transport: &Arc<MemoryTransport>,
+        cache: ResponseCache,
+    ) -> IracingApiClient {
+        IracingApiClient::builder()
+            .credentials("email@example.com", "password")
+            .transport(transport.clone())
+            .cache(cache)
+            .build()
+            .await
+            .unwrap()
+    }
 
-        let raw_data = self
-            .reqwest
-            .get(response.link)
-            .send()
-            .await?
-            .json::<serde_json::Map<String, serde_json::Value>>()
-            .await?;
+    #[tokio::test]
+    async fn client_serves_cached_responses() {
+        let dir = tempfile::tempdir().unwrap();
+        let transport = transport();
+        transport.link(
+            "/data/carclass/get",
+            &serde_json::from_str(include_str!("../test_files/car_class/get.json")).unwrap(),
+        );
+
+        let client = cached_client(&transport, ResponseCache::new(dir.path())).await;
+        client.all_car_classes().await.unwrap();
+        let cached = client.all_car_classes().await.unwrap();
+        assert_eq!(cached.len(), 2);
+        assert_eq!(
+            paths(&transport),
+            ["/auth", "/data/carclass/get", "/links/0"]
+        );
+
+        let bypassing = cached_client(
+            &transport,
+            ResponseCache::new(dir.path()).mode(CacheMode::Bypass),
+        )
+        .await;
+        bypassing.all_car_classes().await.unwrap();
+        assert_eq!(paths(&transport).len(), 6);
+
+        let uncached = cached_client(
+            &transport,
+            ResponseCache::new(dir.path()).ttl("/data/carclass/get", None),
+        )
+        .await;
+        uncached.all_car_classes().await.unwrap();
+        assert_eq!(paths(&transport).len(), 9);
+    }
+
+    #[tokio::test]
+    async fn client_ignores_cache_write_failures() {
+        // The cache directory cannot be created, because a file is in the way
+        let file = tempfile::NamedTempFile::new().unwrap();
+        let transport = transport();
+        transport.link(
+            "/data/carclass/get",
+            &serde_json::from_str(include_str!("../test_files/car_class/get.json")).unwrap(),
+        );
+
+        let client = cached_client(
+            &transport,
+            ResponseCache::new(file.path()).mode(CacheMode::Bypass),
+        )
+        .await;
+        assert_eq!(client.all_car_classes().await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn client_serves_only_cached_responses() {
+        let dir = tempfile::tempdir().unwrap();
+        let transport = transport();
+        transport.link(
+            "/data/carclass/get",
+            &serde_json::from_str(include_str!("../test_files/car_class/get.json")).unwrap(),
+        );
+        let caching = cached_client(&transport, ResponseCache::new(dir.path())).await;
+        caching.all_car_classes().await.unwrap();
+
+        let client = cached_client(
+            &transport,
+            ResponseCache::new(dir.path()).mode(CacheMode::CacheOnly),
+        )
+        .await;
+        let requests = transport.requests().len();
+        assert_eq!(client.all_car_classes().await.unwrap().len(), 2);
+        let err = client.all_cars().await.unwrap_err();
+        assert!(matches!(err, ApiError::CacheMiss { .. }));
+        assert_eq!(err.endpoint(), "/data/car/get");
+        assert_eq!(transport.requests().len(), requests);
+    }
+
+    #[tokio::test]
+    async fn client_link_retry_policy_overrides_retry_policy() {
+        let transport = transport();
+        let data = RetryPolicy::default().max_attempts(5);
+        let link = RetryPolicy::none();
+
+        let client = IracingApiClient::builder()
+            .credentials("email@example.com", "password")
+            .transport(transport.clone())
+            .retry_policy(data.clone())
+            .build()
+            .await
+            .unwrap();
+        assert_eq!(client.link_retry_policy, data);
+
+        for builder in [
+            IracingApiClient::builder()
+                .link_retry_policy(link.clone())
+                .retry_policy(data.clone()),
+            IracingApiClient::builder()
+                .retry_policy(data.clone())
+                .link_retry_policy(link.clone()),
+        ] {
+            let client = builder
+                .credentials("email@example.com", "password")
+                .transport(transport.clone())
+                .build()
+                .await
+                .unwrap();
+            assert_eq!(client.retry_policy, data);
+            assert_eq!(client.link_retry_policy, link);
+        }
+    }
+
+    #[tokio::test]
+    async fn client_serves_recent_responses() {
+        let transport = transport();
+        transport.link(
+            "/data/carclass/get",
+            &serde_json::from_str(include_str!("../test_files/car_class/get.json")).unwrap(),
+        );
+        let client = IracingApiClient::builder()
+            .credentials("email@example.com", "password")
+            .transport(transport.clone())
+            .recent_responses(8, Duration::from_secs(60))
+            .build()
+            .await
+            .unwrap();
+
+        let (first, second) = tokio::join!(client.all_car_classes(), client.all_car_classes());
+        assert_eq!(first.unwrap().len(), 2);
+        assert_eq!(second.unwrap().len(), 2);
+        client.all_car_classes().await.unwrap();
+        assert_eq!(
+            paths(&transport),
+            ["/auth", "/data/carclass/get", "/links/0"]
+        );
+    }
+
+    #[tokio::test]
+    async fn client_serves_recent_responses_by_default() {
+        let transport = transport();
+        transport.link(
+            "/data/carclass/get",
+            &serde_json::from_str(include_str!("../test_files/car_class/get.json")).unwrap(),
+        );
+        let client = client(&transport).await;
+
+        client.all_car_classes().await.unwrap();
+        client.all_car_classes().await.unwrap();
+        assert_eq!(
+            paths(&transport),
+            ["/auth", "/data/carclass/get", "/links/0"]
+        );
+
+        tokio::time::sleep(Duration::from_secs(1)).await;
+        client.all_car_classes().await.unwrap();
+        assert_eq!(transport.requests().len(), 5);
+    }
+
+    #[tokio::test]
+    async fn client_computes_race_starts_beyond_race_guide() {
+        let transport = transport();
+        transport.link(
+            "/data/season/race_guide",
+            &serde_json::from_str(include_str!("../test_files/season/race_guide.json")).unwrap(),
+        );
+        // Move the current race week of the season to today
+        let today = Utc::now().naive_utc().date().to_string();
+        let mut seasons: serde_json::Value =
+            serde_json::from_str(include_str!("../test_files/series/seasons.json")).unwrap();
+        let schedule = &mut seasons[0]["schedules"][1];
+        schedule["start_date"] = today.clone().into();
+        schedule["race_time_descriptors"][0]["start_date"] = today.into();
+        transport.link("/data/series/seasons", &seasons);
+        // Send every request, so that the requests for the schedule are seen
+        let client = IracingApiClient::builder()
+            .credentials("email@example.com", "password")
+            .transport(transport.clone())
+            .recent_responses(0, Duration::ZERO)
+            .build()
+            .await
+            .unwrap();
+
+        let starts = client.next_race_starts(139, 5).await.unwrap();
+        assert_eq!(starts.len(), 5);
+        assert_eq!(
+            starts[0],
+            "2022-01-20T18:45:00Z".parse::<DateTime<Utc>>().unwrap()
+        );
+        assert_eq!(
+            starts[1],
+            "2022-01-20T20:45:00Z".parse::<DateTime<Utc>>().unwrap()
+        );
+        assert!(starts[2] > Utc::now());
+        assert_eq!(starts[3] - starts[2], chrono::Duration::hours(2));
+        assert_eq!(starts[4] - starts[3], chrono::Duration::hours(2));
+
+        // The schedule is only requested when the race guide is not enough
+        let requests = transport.requests().len();
+        assert_eq!(client.next_race_starts(139, 2).await.unwrap().len(), 2);
+        assert_eq!(transport.requests().len(), requests + 2);
+    }
+
+    #[tokio::test]
+    async fn client_defaults_to_own_cust_id() {
+        let transport = transport();
+        transport.link(
+            "/data/stats/member_summary?cust_id=123456",
+            &serde_json::from_str(include_str!("../test_files/stats/member_summary.json")).unwrap(),
+        );
+        let client = client(&transport).await;
+
+        let summary = client.member_summary(None).await.unwrap();
+        assert_eq!(summary.cust_id, 123456);
+        assert!(matches!(
+            client.member_summary(Some(234567)).await,
+            Err(ApiError::NotFound { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn client_sends_session_cookies() {
+        let transport = Arc::new(MemoryTransport::new());
+        let mut login = Response::json(
+            &serde_json::from_str(include_str!("../test_files/auth/success.json")).unwrap(),
+        );
+        login.headers.insert(
+            "set-cookie",
+            "authtoken_members=token; Path=/; Secure".parse().unwrap(),
+        );
+        transport.respond(Method::POST, "/auth", login);
+        transport.link("/data/car/get", &serde_json::json!([]));
+
+        let client = client(&transport).await;
+        client.all_cars().await.unwrap();
+
+        let requests = transport.requests();
+        assert_eq!(requests[1].headers["cookie"], "authtoken_members=token");
+        // Links are presigned, and must not receive the session cookies
+        assert!(requests[2].headers.get("cookie").is_none());
+        let cookies = client.save_session().cookies;
+        assert_eq!(cookies.len(), 1);
+        assert_eq!(
+            (cookies[0].name.as_str(), cookies[0].value.as_str()),
+            ("authtoken_members", "token")
+        );
+        assert!(cookies[0].secure);
+    }
+
+    fn saved_session() -> SavedSession {
+        SavedSession {
+            auth: serde_json::from_str(include_str!("../test_files/auth/success.json")).unwrap(),
+            cookies: vec![SavedCookie {
+                name: "authtoken_members".to_string(),
+                value: "saved".to_string(),
+                domain: None,
+                path: Some("/".to_string()),
+                expires: None,
+                secure: true,
+            }],
+        }
+    }
+
+    #[tokio::test]
+    async fn client_restores_valid_session() {
+        let transport = Arc::new(MemoryTransport::new());
+        transport.respond(
+            Method::GET,
+            "/data/member/info",
+            Response::new(StatusCode::OK, "{}"),
+        );
 
-        // Convert from a `serde_json::Map` to a `HashMap`
-        let map = raw_data
-            .into_iter()
-            .map(|(car_id, assets)| {
-                (
-                    car_id.parse().unwrap(),
-                    serde_json::from_value(assets).unwrap(),
-                )
+        let client = IracingApiClient::builder()
+            .session(saved_session())
+            .transport(transport.clone())
+            .build()
+            .await
+            .unwrap();
+
+        let requests = transport.requests();
+        assert_eq!(paths(&transport), ["/data/member/info"]);
+        assert_eq!(requests[0].headers["cookie"], "authtoken_members=saved");
+        assert_eq!(client.save_session(), saved_session());
+    }
+
+    #[tokio::test]
+    async fn client_logs_in_when_session_is_not_accepted() {
+        for status in [
+            StatusCode::UNAUTHORIZED,
+            StatusCode::FOUND,
+            StatusCode::TOO_MANY_REQUESTS,
+            StatusCode::SERVICE_UNAVAILABLE,
+        ] {
+            let transport = transport();
+            transport.respond(Method::GET, "/data/member/info", Response::new(status, ""));
+            let relogins = Arc::new(AtomicU32::new(0));
+
+            let hook_relogins = relogins.clone();
+            IracingApiClient::builder()
+                .credentials("email@example.com", "password")
+                .session(saved_session())
+                .on_relogin(move |_| {
+                    hook_relogins.fetch_add(1, Ordering::SeqCst);
+                })
+                .transport(transport.clone())
+                .build()
+                .await
+                .unwrap();
+
+            assert_eq!(paths(&transport), ["/data/member/info", "/auth"]);
+            assert_eq!(relogins.load(Ordering::SeqCst), 1);
+        }
+    }
+
+    #[tokio::test]
+    async fn client_does_not_restore_expired_session() {
+        let transport = transport();
+        let mut session = saved_session();
+        session.cookies[0].expires = Some(chrono::Utc::now() - chrono::Duration::seconds(1));
+
+        let result = IracingApiClient::builder()
+            .session(session.clone())
+            .transport(transport.clone())
+            .build()
+            .await;
+        assert!(matches!(result, Err(ClientInitError::SessionExpired)));
+
+        IracingApiClient::builder()
+            .credentials("email@example.com", "password")
+            .session(session)
+            .transport(transport.clone())
+            .build()
+            .await
+            .unwrap();
+        assert_eq!(paths(&transport), ["/auth"]);
+    }
+
+    #[tokio::test]
+    async fn client_logs_in_again_when_unauthorized() {
+        let transport = transport();
+        transport.respond(
+            Method::GET,
+            "/data/car/get",
+            Response::new(StatusCode::UNAUTHORIZED, r#"{"error":"Unauthorized"}"#),
+        );
+        transport.link("/data/car/get", &serde_json::json!([]));
+
+        let relogins = Arc::new(AtomicU32::new(0));
+        let hook_relogins = relogins.clone();
+        let client = IracingApiClient::builder()
+            .credentials("email@example.com", "password")
+            .transport(transport.clone())
+            .on_relogin(move |_| {
+                hook_relogins.fetch_add(1, Ordering::SeqCst);
             })
-            .collect();
-        Ok(map)
+            .build()
+            .await
+            .unwrap();
+
+        client.all_cars().await.unwrap();
+        assert_eq!(relogins.load(Ordering::SeqCst), 1);
+        assert_eq!(
+            paths(&transport),
+            [
+                "/auth",
+                "/data/car/get",
+                "/auth",
+                "/data/car/get",
+                "/links/0"
+            ]
+        );
     }
-}
 
-#[derive(Error, Debug)]
-pub enum ClientInitError {
-    #[error("Cannot initialize HTTP client")]
-    ReqwestInitError(reqwest::Error),
-    #[error("Connection failure")]
-    ConnectionFailure(reqwest::Error),
-    #[error("Authentication with iRacing failed")]
-    AuthenticationFailure(#[from] AuthError),
+    #[tokio::test]
+    async fn client_retries_only_the_failed_link_fetch() {
+        let transport = transport();
+        transport.respond(
+            Method::GET,
+            "/data/car/get",
+            Response::json(&serde_json::json!({ "link": "https://memory.invalid/flaky" })),
+        );
+        transport.respond(
+            Method::GET,
+            "/flaky",
+            Response::new(StatusCode::SERVICE_UNAVAILABLE, ""),
+        );
+        transport.respond(
+            Method::GET,
+            "/flaky",
+            Response::json(&serde_json::json!([])),
+        );
+
+        let client = client(&transport).await;
+        client.all_cars().await.unwrap();
+        assert_eq!(
+            paths(&transport),
+            ["/auth", "/data/car/get", "/flaky", "/flaky"]
+        );
+    }
+
+    #[tokio::test]
+    async fn client_reports_link_fetch_failure() {
+        let transport = transport();
+        transport.respond(
+            Method::GET,
+            "/data/car/get",
+            Response::json(&serde_json::json!({ "link": "https://memory.invalid/gone" })),
+        );
+        transport.respond(
+            Method::GET,
+            "/gone",
+            Response::new(StatusCode::FORBIDDEN, ""),
+        );
+
+        let client = client(&transport).await;
+        let err = client.all_cars().await.unwrap_err();
+        assert!(matches!(err, ApiError::LinkFetchFailure { .. }));
+        assert_eq!(err.status(), Some(StatusCode::FORBIDDEN));
+    }
+
+    #[tokio::test]
+    async fn client_reports_not_found() {
+        let transport = transport();
+        let client = client(&transport).await;
+        let err = client.session_results(1, false).await.unwrap_err();
+        assert!(matches!(err, ApiError::NotFound { .. }));
+        assert_eq!(err.endpoint(), "/data/results/get");
+    }
+
+    #[tokio::test]
+    async fn client_tracks_rate_limit() {
+        let transport = transport();
+        let mut response =
+            Response::json(&serde_json::json!({ "link": "https://memory.invalid/cars" }));
+        response
+            .headers
+            .insert("x-ratelimit-limit", "240".parse().unwrap());
+        response
+            .headers
+            .insert("x-ratelimit-remaining", "100".parse().unwrap());
+        response
+            .headers
+            .insert("x-ratelimit-reset", "4102444800".parse().unwrap());
+        transport.respond(Method::GET, "/data/car/get", response);
+        transport.respond(Method::GET, "/cars", Response::json(&serde_json::json!([])));
+
+        let client = client(&transport).await;
+        assert_eq!(client.rate_limit(), None);
+        client.all_cars().await.unwrap();
+        let rate_limit = client.rate_limit().unwrap();
+        assert_eq!((rate_limit.limit, rate_limit.remaining), (240, 100));
+    }
+
+    fn http(base_url: &str) -> Http {
+        Http {
+            transport: Box::new(MemoryTransport::new()),
+            base_url: Url::parse(base_url).unwrap(),
+            cookies: SessionCookies::default(),
+        }
+    }
+
+    #[test]
+    fn http_url() {
+        assert_eq!(
+            http("https://members-ng.iracing.com")
+                .url("/data/car/get", &[])
+                .as_str(),
+            "https://members-ng.iracing.com/data/car/get"
+        );
+        assert_eq!(
+            http("http://localhost:8080/mock/")
+                .url("/data/results/get", &[("subsession_id", "1".to_string())])
+                .as_str(),
+            "http://localhost:8080/mock/data/results/get?subsession_id=1"
+        );
+    }
+
+    #[test]
+    fn check_status_success() {
+        assert!(check_status("/data/car/get", StatusCode::OK).is_ok());
+    }
+
+    #[test]
+    fn check_status_errors() {
+        let err = check_status("/data/results/get", StatusCode::NOT_FOUND).unwrap_err();
+        assert!(matches!(err, ApiError::NotFound { .. }));
+        assert_eq!(err.endpoint(), "/data/results/get");
+
+        let err = check_status("/data/results/get", StatusCode::UNAUTHORIZED).unwrap_err();
+        assert!(matches!(err, ApiError::Unauthorized { .. }));
+
+        let err = check_status("/data/results/get", StatusCode::TOO_MANY_REQUESTS).unwrap_err();
+        assert!(matches!(err, ApiError::RateLimited { .. }));
+
+        let err = check_status("/data/results/get", StatusCode::BAD_GATEWAY).unwrap_err();
+        assert!(matches!(err, ApiError::UnexpectedStatus { .. }));
+        assert_eq!(err.status(), Some(StatusCode::BAD_GATEWAY));
+    }
+
+    #[tokio::test]
+    async fn client_replays_fixtures() {
+        let client = IracingApiClient::builder()
+            .credentials("email@example.com", "password")
+            .replay_fixtures("test_files/replay")
+            .build()
+            .await
+            .unwrap();
+
+        let result = client.session_results(38280997, false).await.unwrap();
+        assert_eq!(result.subsession_id, 38280997);
+        assert_eq!(
+            result.session_results[0].results[0].display_name,
+            "Test Driver"
+        );
+        assert_eq!(client.rate_limit().unwrap().remaining, 239);
+    }
+
+    #[tokio::test]
+    async fn client_replays_season_results_fixtures() {
+        let client = IracingApiClient::builder()
+            .credentials("email@example.com", "password")
+            .replay_fixtures("test_files/replay")
+            .build()
+            .await
+            .unwrap();
+
+        let results = client
+            .season_results(
+                SeasonResultsQuery::new(3416)
+                    .event_type(EventType::Race)
+                    .race_week_num(2),
+            )
+            .await
+            .unwrap();
+        assert_eq!(results.season_id, 3416);
+        assert_eq!(results.results_list.len(), 3);
+        assert_eq!(results.results_list[1].subsession_id, 43104518);
+        assert_eq!(results.results_list[1].track.track_name, "Lime Rock Park");
+        assert_eq!(client.rate_limit().unwrap().remaining, 238);
+    }
+
+    #[tokio::test]
+    async fn client_replays_car_fixtures() {
+        let client = IracingApiClient::builder()
+            .credentials("email@example.com", "password")
+            .replay_fixtures("test_files/replay")
+            .build()
+            .await
+            .unwrap();
+
+        let cars = client.all_cars().await.unwrap();
+        assert_eq!(cars.len(), 2);
+        assert_eq!(cars[0].car_id, 67);
+        assert_eq!(cars[0].car_dirpath, "mx5\\mx52016");
+        assert_eq!(cars[1].car_name, "Skip Barber Formula 2000");
+    }
+
+    #[tokio::test]
+    async fn client_records_fixtures_for_replay() {
+        let dir = tempfile::tempdir().unwrap();
+        let transport = transport();
+        transport.link(
+            "/data/carclass/get",
+            &serde_json::from_str(include_str!("../test_files/car_class/get.json")).unwrap(),
+        );
+
+        let recording = IracingApiClient::builder()
+            .credentials("email@example.com", "password")
+            .transport(transport.clone())
+            .record_fixtures(dir.path())
+            .build()
+            .await
+            .unwrap();
+        let recorded = recording.all_car_classes().await.unwrap();
+
+        let auth = std::fs::read_dir(dir.path().join("auth"))
+            .unwrap()
+            .map(|entry| std::fs::read_to_string(entry.unwrap().path()).unwrap())
+            .collect::<String>();
+        assert!(!auth.contains("email@example.com"));
+        assert!(!auth.contains("sso cookie value"));
+
+        let replaying = IracingApiClient::builder()
+            .credentials("email@example.com", "password")
+            .replay_fixtures(dir.path())
+            .build()
+            .await
+            .unwrap();
+        let replayed = replaying.all_car_classes().await.unwrap();
+        assert_eq!(
+            serde_json::to_value(recorded).unwrap(),
+            serde_json::to_value(replayed).unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn client_ignores_fixture_write_failures() {
+        // The fixture directory cannot be created, because a file is in the way
+        let file = tempfile::NamedTempFile::new().unwrap();
+        let transport = transport();
+        transport.link(
+            "/data/carclass/get",
+            &serde_json::from_str(include_str!("../test_files/car_class/get.json")).unwrap(),
+        );
+
+        let client = IracingApiClient::builder()
+            .credentials("email@example.com", "password")
+            .transport(transport.clone())
+            .record_fixtures(file.path())
+            .build()
+            .await
+            .unwrap();
+        assert_eq!(client.all_car_classes().await.unwrap().len(), 2);
+    }
+
+    fn chunked_transport(second_chunk: Response) -> Arc<MemoryTransport> {
+        let transport = transport();
+        transport.link(
+            "/data/results/search_series",
+            &serde_json::json!({
+                "type": "search_series",
+                "data": {
+                    "success": true,
+                    "chunk_info": {
+                        "chunk_size": 2,
+                        "num_chunks": 2,
+                        "rows": 3,
+                        "base_download_url": "https://memory.invalid/chunks/",
+                        "chunk_file_names": ["0.json", "1.json"]
+                    }
+                }
+            }),
+        );
+        transport.respond(
+            Method::GET,
+            "/chunks/0.json",
+            Response::json(&serde_json::json!([1, 2])),
+        );
+        transport.respond(Method::GET, "/chunks/1.json", second_chunk);
+        transport
+    }
+
+    #[derive(serde::Deserialize)]
+    struct SearchHeader {
+        data: SearchData,
+    }
+
+    #[derive(serde::Deserialize)]
+    struct SearchData {
+        chunk_info: Option<ChunkInfo>,
+    }
+
+    struct SearchSeries;
+
+    impl Endpoint for SearchSeries {
+        type Response = SearchHeader;
+        const PATH: &'static str = "/data/results/search_series";
+    }
+
+    impl ChunkedEndpoint for SearchSeries {
+        type Row = u32;
+
+        fn chunk_info(response: &SearchHeader) -> Option<&ChunkInfo> {
+            response.data.chunk_info.as_ref()
+        }
+    }
+
+    async fn search_chunk_info(client: &IracingApiClient) -> ChunkInfo {
+        let header = client.request(&SearchSeries).await.unwrap();
+        header.data.chunk_info.unwrap()
+    }
+
+    #[tokio::test]
+    async fn client_requests_chunked_endpoint() {
+        let transport = chunked_transport(Response::json(&serde_json::json!([3])));
+        let client = client(&transport).await;
+
+        let chunked = client.request_chunked(&SearchSeries).await.unwrap();
+        assert_eq!(chunked.response.data.chunk_info.unwrap().rows, 3);
+        assert_eq!(chunked.rows, [1, 2, 3]);
+
+        let (_, rows) = client.request_stream(&SearchSeries).await.unwrap();
+        let rows: Vec<u32> = rows.try_collect().await.unwrap();
+        assert_eq!(rows, [1, 2, 3]);
+    }
+
+    #[tokio::test]
+    async fn client_requests_chunked_endpoint_without_chunks() {
+        let transport = transport();
+        transport.link(
+            "/data/results/search_series",
+            &serde_json::json!({ "data": { "chunk_info": null } }),
+        );
+        let client = client(&transport).await;
+
+        let chunked = client.request_chunked(&SearchSeries).await.unwrap();
+        assert!(chunked.rows.is_empty());
+    }
+
+    #[tokio::test]
+    async fn client_fetches_chunks_in_order() {
+        let transport = chunked_transport(Response::json(&serde_json::json!([3])));
+        let client = client(&transport).await;
+        let chunk_info = search_chunk_info(&client).await;
+
+        let rows: Vec<u32> = client
+            .fetch_chunks("/data/results/search_series", &chunk_info)
+            .await
+            .unwrap();
+        assert_eq!(rows, [1, 2, 3]);
+
+        let streamed: Vec<u32> = client
+            .chunk_stream::<u32>("/data/results/search_series", &chunk_info)
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(streamed, [1, 2, 3]);
+    }
+
+    #[tokio::test]
+    async fn client_fetches_lap_data() {
+        let transport = transport();
+        let mut header: serde_json::Value =
+            serde_json::from_str(include_str!("../test_files/lap_data/lap_data.json")).unwrap();
+        header["chunk_info"]["base_download_url"] = "https://memory.invalid/chunks/".into();
+        transport.link(
+            "/data/results/lap_data?subsession_id=38280997&simsession_number=0&cust_id=123456",
+            &header,
+        );
+        transport.respond(
+            Method::GET,
+            "/chunks/0123456789abcdef.json",
+            Response::new(
+                StatusCode::OK,
+                include_str!("../test_files/lap_data/chunk.json"),
+            ),
+        );
+        let client = client(&transport).await;
+
+        let lap_data = client
+            .lap_data(38280997, 0, Entrant::Driver(123456))
+            .await
+            .unwrap();
+        assert_eq!(lap_data.response.name, "Test Driver");
+        assert_eq!(lap_data.rows.len(), 3);
+        assert_eq!(lap_data.rows[2].lap_number, 2);
+    }
+
+    #[tokio::test]
+    async fn client_rejects_invalid_search() {
+        let transport = transport();
+        let client = client(&transport).await;
+
+        let query = SeriesSearchQuery::new().series_id(139);
+        let err = client.search_series(&query).await.err().unwrap();
+        assert!(matches!(
+            err,
+            ApiError::InvalidQuery {
+                source: QueryError::MissingTimeFrame,
+                ..
+            }
+        ));
+        assert_eq!(err.endpoint(), "/data/results/search_series");
+        assert_eq!(paths(&transport), ["/auth"]);
+    }
+
+    #[tokio::test]
+    async fn client_streams_search_results() {
+        let transport = transport();
+        let mut header: serde_json::Value =
+            serde_json::from_str(include_str!("../test_files/search/search_series.json")).unwrap();
+        header["data"]["chunk_info"]["base_download_url"] = "https://memory.invalid/chunks/".into();
+        transport.link(
+            "/data/results/search_series?season_year=2022&season_quarter=1",
+            &header,
+        );
+        transport.respond(
+            Method::GET,
+            "/chunks/aabbccddeeff.json",
+            Response::new(
+                StatusCode::OK,
+                include_str!("../test_files/search/search_series_chunk.json"),
+            ),
+        );
+        let client = client(&transport).await;
+
+        let rows: Vec<_> = client
+            .search_series(&SeriesSearchQuery::new().season(2022, 1))
+            .await
+            .unwrap()
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[1].display_name.as_deref(), Some("Other Driver"));
+    }
+
+    #[tokio::test]
+    async fn client_reports_chunk_failure() {
+        let transport = chunked_transport(Response::new(StatusCode::FORBIDDEN, ""));
+        let client = client(&transport).await;
+        let chunk_info = search_chunk_info(&client).await;
+
+        let rows: Vec<Result<u32, ApiError>> = client
+            .chunk_stream("/data/results/search_series", &chunk_info)
+            .collect()
+            .await;
+        assert!(matches!(
+            rows[..],
+            [Ok(1), Ok(2), Err(ApiError::LinkFetchFailure { .. })]
+        ));
+    }
+
+    #[tokio::test]
+    async fn client_records_chunks_for_replay() {
+        let dir = tempfile::tempdir().unwrap();
+        let transport = chunked_transport(Response::json(&serde_json::json!([3])));
+        let recording = IracingApiClient::builder()
+            .credentials("email@example.com", "password")
+            .transport(transport.clone())
+            .record_fixtures(dir.path())
+            .build()
+            .await
+            .unwrap();
+        let chunk_info = search_chunk_info(&recording).await;
+        let _: Vec<u32> = recording
+            .fetch_chunks("/data/results/search_series", &chunk_info)
+            .await
+            .unwrap();
+
+        let replaying = IracingApiClient::builder()
+            .credentials("email@example.com", "password")
+            .replay_fixtures(dir.path())
+            .build()
+            .await
+            .unwrap();
+        let chunk_info = search_chunk_info(&replaying).await;
+        assert!(chunk_info
+            .base_download_url
+            .starts_with("https://fixtures.invalid/"));
+        let rows: Vec<u32> = replaying
+            .fetch_chunks("/data/results/search_series", &chunk_info)
+            .await
+            .unwrap();
+        assert_eq!(rows, [1, 2, 3]);
+    }
 }
@@ -0,0 +1,327 @@
+use super::{
+    ClientInitError, Coalescer, CredentialProvider, Http, IracingApiClient, LoginHook,
+    SavedSession, SessionCookies, BASE_URL,
+};
+use crate::{
+    cache::ResponseCache,
+    model::auth::{AuthRequestBody, AuthSuccessBody},
+    rate_limit::RateLimiter,
+    retry::RetryPolicy,
+    transport::{RecordingTransport, ReplayTransport, ReqwestTransport, Transport},
+};
+use std::{
+    path::PathBuf,
+    sync::{atomic::AtomicU64, RwLock},
+    time::Duration,
+};
+use {
+    reqwest::{redirect::Policy, Client, Url},
+    tokio::sync::Mutex,
+};
+
+const USER_AGENT: &str = concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION"));
+
+/// Configures and authenticates an [IracingApiClient]
+///
+/// ```no_run
+/// # async fn example() -> Result<(), iracing_api::client::ClientInitError> {
+/// use std::time::Duration;
+/// use iracing_api::IracingApiClient;
+///
+/// let client = IracingApiClient::builder()
+///     .credentials("email@example.com", "password")
+///     .timeout(Duration::from_secs(30))
+///     .user_agent("my-league-bot/1.0")
+///     .build()
+///     .await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct IracingApiClientBuilder {
+    credentials: Option<Box<dyn CredentialProvider>>,
+    session: Option<SavedSession>,
+    base_url: String,
+    timeout: Option<Duration>,
+    connect_timeout: Option<Duration>,
+    user_agent: String,
+    transport: Option<Box<dyn Transport>>,
+    record_fixtures: Option<PathBuf>,
+    replay_fixtures: Option<PathBuf>,
+    on_relogin: Option<LoginHook>,
+    wait_for_rate_limit: bool,
+    retry_policy: RetryPolicy,
+    link_retry_policy: Option<RetryPolicy>,
+    chunk_concurrency: usize,
+    cache: Option<ResponseCache>,
+    recent_responses: (usize, Duration),
+}
+
+impl Default for IracingApiClientBuilder {
+    fn default() -> Self {
+        Self {
+            credentials: None,
+            session: None,
+            base_url: BASE_URL.to_string(),
+            timeout: None,
+            connect_timeout: None,
+            user_agent: USER_AGENT.to_string(),
+            transport: None,
+            record_fixtures: None,
+            replay_fixtures: None,
+            on_relogin: None,
+            wait_for_rate_limit: false,
+            retry_policy: RetryPolicy::default(),
+            link_retry_policy: None,
+            chunk_concurrency: 4,
+            cache: None,
+            recent_responses: (16, Duration::from_secs(1)),
+        }
+    }
+}
+
+impl IracingApiClientBuilder {
+    /// Log in with an email and password. The password is hashed before it
+    /// is sent or stored.
+    pub fn credentials(self, email: &str, password: &str) -> Self {
+        self.credential_provider(AuthRequestBody::new(email, password))
+    }
+
+    /// Log in with an email and a password already hashed with
+    /// [hash_password](crate::model::auth::hash_password)
+    pub fn hashed_credentials(self, email: &str, hashed_password: &str) -> Self {
+        self.credential_provider(AuthRequestBody::with_hashed_password(
+            email,
+            hashed_password,
+        ))
+    }
+
+    /// Fetch the credentials from `provider` every time the client needs to
+    /// log in. The provided password must already be hashed, e.g. by creating
+    /// the credentials with [AuthRequestBody::new].
+    pub fn credential_provider(mut self, provider: impl CredentialProvider + 'static) -> Self {
+        self.credentials = Some(Box::new(provider));
+        self
+    }
+
+    /// Restore a session saved with [IracingApiClient::save_session] instead
+    /// of logging in
+    ///
+    /// When the client is built, the session is checked with a request to
+    /// iRacing. If it has expired or cannot be checked, the client logs in
+    /// with its credentials instead, and calls the
+    /// [on_relogin](Self::on_relogin) hook.
+    pub fn session(mut self, session: SavedSession) -> Self {
+        self.session = Some(session);
+        self
+    }
+
+    /// The URL that `/auth` and `/data` paths are relative to. Defaults to
+    /// `https://members-ng.iracing.com`.
+    pub fn base_url(mut self, base_url: &str) -> Self {
+        self.base_url = base_url.to_string();
+        self
+    }
+
+    /// The timeout of each request, from connecting until the response body
+    /// has been read
+    ///
+    /// Ignored if a client or transport is provided with
+    /// [reqwest_client](Self::reqwest_client) or [transport](Self::transport).
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// The timeout for establishing a connection
+    ///
+    /// Ignored if a client or transport is provided with
+    /// [reqwest_client](Self::reqwest_client) or [transport](Self::transport).
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// The `User-Agent` header sent with every request. Defaults to
+    /// `iracing-api/<version>`.
+    ///
+    /// Ignored if a client or transport is provided with
+    /// [reqwest_client](Self::reqwest_client) or [transport](Self::transport).
+    pub fn user_agent(mut self, user_agent: &str) -> Self {
+        self.user_agent = user_agent.to_string();
+        self
+    }
+
+    /// Send requests through a preconfigured client, e.g. one using a proxy.
+    ///
+    /// The client does not need a cookie store, the session cookies are
+    /// managed by the [IracingApiClient]. It should not follow redirects, e.g.
+    /// by building it with [reqwest::redirect::Policy::none]: the session
+    /// cookies would be sent to the redirect target, and a redirect to the
+    /// login page would hide an expired session.
+    pub fn reqwest_client(self, client: Client) -> Self {
+        self.transport(ReqwestTransport::new(client))
+    }
+
+    /// Send requests through a custom [Transport], e.g. a
+    /// [MemoryTransport](crate::transport::MemoryTransport) in tests
+    pub fn transport(mut self, transport: impl Transport + 'static) -> Self {
+        self.transport = Some(Box::new(transport));
+        self
+    }
+
+    /// Write every response to a fixture directory, so that it can be served
+    /// later with [replay_fixtures](Self::replay_fixtures)
+    ///
+    /// Emails, auth codes, tokens and cookies are scrubbed from the recorded
+    /// responses. See [RecordingTransport] for details.
+    pub fn record_fixtures(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.record_fixtures = Some(dir.into());
+        self
+    }
+
+    /// Serve responses recorded with [record_fixtures](Self::record_fixtures)
+    /// instead of sending any request. Overrides any other transport.
+    pub fn replay_fixtures(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.replay_fixtures = Some(dir.into());
+        self
+    }
+
+    /// Register a hook called with the new authentication data every time the
+    /// client has to log in again because the session expired
+    pub fn on_relogin(mut self, hook: impl Fn(&AuthSuccessBody) + Send + Sync + 'static) -> Self {
+        self.on_relogin = Some(Box::new(hook));
+        self
+    }
+
+    /// Wait for the rate limit to reset when iRacing reports that no requests
+    /// remain, instead of sending requests that would be throttled
+    pub fn wait_for_rate_limit(mut self, wait: bool) -> Self {
+        self.wait_for_rate_limit = wait;
+        self
+    }
+
+    /// Set the retry policy used for `/data` requests, and for fetching the
+    /// links they return unless a [link_retry_policy](Self::link_retry_policy)
+    /// is set. Defaults to [RetryPolicy::default].
+    pub fn retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry_policy = policy;
+        self
+    }
+
+    /// Set the retry policy used only for fetching the links returned by
+    /// `/data` requests, overriding the one set by
+    /// [retry_policy](Self::retry_policy) regardless of the order they are
+    /// set in
+    pub fn link_retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.link_retry_policy = Some(policy);
+        self
+    }
+
+    /// The maximum number of chunk files downloaded at once when fetching
+    /// chunked data. Defaults to 4, values below 1 are treated as 1.
+    pub fn chunk_concurrency(mut self, concurrency: usize) -> Self {
+        self.chunk_concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Serve responses from an on-disk cache when possible, see
+    /// [ResponseCache]. Responses are not cached by default.
+    pub fn cache(mut self, cache: ResponseCache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Remember up to `capacity` recently received responses, and serve them
+    /// again to identical requests made within `max_age`
+    ///
+    /// Identical requests made at the same time always share a single request
+    /// to iRacing, this extends the sharing to requests made shortly after.
+    /// Defaults to 16 responses for one second, a `capacity` of 0 disables it.
+    pub fn recent_responses(mut self, capacity: usize, max_age: Duration) -> Self {
+        self.recent_responses = (capacity, max_age);
+        self
+    }
+
+    /// Create the client and authenticate with the iRacing service, or restore
+    /// the saved session if one was provided and is still valid
+    ///
+    /// # Errors
+    ///
+    /// This method fails if no credentials were provided and there is no
+    /// valid saved session, the base URL is invalid, or the HTTP client cannot
+    /// be initialized. See [IracingApiClient::new] for the ways authentication
+    /// can fail.
+    pub async fn build(self) -> Result<IracingApiClient, ClientInitError> {
+        let base_url = Url::parse(&self.base_url).map_err(ClientInitError::InvalidBaseUrl)?;
+
+        let transport = match (self.replay_fixtures, self.transport) {
+            (Some(dir), _) => Box::new(ReplayTransport::new(dir)),
+            (None, Some(transport)) => transport,
+            (None, None) => {
+                let mut builder = Client::builder()
+                    .user_agent(self.user_agent)
+                    .redirect(Policy::none());
+                if let Some(timeout) = self.timeout {
+                    builder = builder.timeout(timeout);
+                }
+                if let Some(timeout) = self.connect_timeout {
+                    builder = builder.connect_timeout(timeout);
+                }
+                let client = builder.build().map_err(ClientInitError::ReqwestInitError)?;
+                Box::new(ReqwestTransport::new(client))
+            }
+        };
+        let transport = match self.record_fixtures {
+            Some(dir) => Box::new(RecordingTransport::new(transport, dir)),
+            None => transport,
+        };
+
+        let http = Http {
+            transport,
+            base_url,
+            cookies: SessionCookies::default(),
+        };
+        let rate_limiter = RateLimiter::new(self.wait_for_rate_limit);
+        let auth = match (self.session, &self.credentials) {
+            (Some(session), credentials) => {
+                let valid = if session.is_expired() {
+                    Ok(false)
+                } else {
+                    http.restore_cookies(&session);
+                    http.session_is_valid(&rate_limiter).await
+                };
+                match (valid, credentials) {
+                    (Ok(true), _) => session.auth,
+                    (_, Some(credentials)) => {
+                        let auth = http.login(&credentials.credentials()).await?;
+                        if let Some(hook) = &self.on_relogin {
+                            hook(&auth);
+                        }
+                        auth
+                    }
+                    (Ok(false), None) => return Err(ClientInitError::SessionExpired),
+                    (Err(err), None) => return Err(ClientInitError::ConnectionFailure(err)),
+                }
+            }
+            (None, Some(credentials)) => http.login(&credentials.credentials()).await?,
+            (None, None) => return Err(ClientInitError::MissingCredentials),
+        };
+
+        Ok(IracingApiClient {
+            http,
+            credentials: self.credentials,
+            auth: RwLock::new(auth),
+            auth_generation: AtomicU64::new(0),
+            login_lock: Mutex::new(()),
+            on_relogin: self.on_relogin,
+            rate_limiter,
+            link_retry_policy: self
+                .link_retry_policy
+                .unwrap_or_else(|| self.retry_policy.clone()),
+            retry_policy: self.retry_policy,
+            chunk_concurrency: self.chunk_concurrency,
+            cache: self.cache,
+            coalescer: Coalescer::new(self.recent_responses.0, self.recent_responses.1),
+        })
+    }
+}
@@ -0,0 +1,216 @@
+use super::ApiError;
+use std::{
+    collections::{HashMap, VecDeque},
+    future::Future,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+use tokio::sync::watch;
+
+type SharedResult = Result<Arc<Vec<u8>>, ApiError>;
+
+/// Shares the result of a request between every caller making the same
+/// request at the same time, and remembers the most recent successful results
+pub(crate) struct Coalescer {
+    in_flight: Mutex<HashMap<String, watch::Receiver<Option<SharedResult>>>>,
+    recent: Mutex<VecDeque<RecentResponse>>,
+    capacity: usize,
+    max_age: Duration,
+}
+
+struct RecentResponse {
+    key: String,
+    completed_at: Instant,
+    body: Arc<Vec<u8>>,
+}
+
+enum Role {
+    Leader(watch::Sender<Option<SharedResult>>),
+    Follower(watch::Receiver<Option<SharedResult>>),
+}
+
+/// Removes an in-flight request when its leader completes or is cancelled,
+/// so that followers of a cancelled request can take over
+struct InFlightGuard<'a> {
+    coalescer: &'a Coalescer,
+    key: &'a str,
+}
+
+impl Drop for InFlightGuard<'_> {
+    fn drop(&mut self) {
+        self.coalescer.in_flight.lock().unwrap().remove(self.key);
+    }
+}
+
+impl Coalescer {
+    /// Remember up to `capacity` successful results for `max_age`
+    pub(crate) fn new(capacity: usize, max_age: Duration) -> Self {
+        Self {
+            in_flight: Mutex::new(HashMap::new()),
+            recent: Mutex::new(VecDeque::new()),
+            capacity,
+            max_age,
+        }
+    }
+
+    /// Run `fetch`, unless a request with the same `key` is in flight or
+    /// recently completed, in which case its result is returned instead
+    pub(crate) async fn run<F, Fut>(&self, key: String, fetch: F) -> Result<Vec<u8>, ApiError>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<Vec<u8>, ApiError>>,
+    {
+        let mut fetch = Some(fetch);
+        loop {
+            if let Some(body) = self.recent(&key) {
+                return Ok(body.to_vec());
+            }
+
+            let role = {
+                let mut in_flight = self.in_flight.lock().unwrap();
+                match in_flight.get(&key) {
+                    Some(receiver) => Role::Follower(receiver.clone()),
+                    None => {
+                        let (sender, receiver) = watch::channel(None);
+                        in_flight.insert(key.clone(), receiver);
+                        Role::Leader(sender)
+                    }
+                }
+            };
+
+            match role {
+                Role::Leader(sender) => {
+                    let _guard = InFlightGuard {
+                        coalescer: self,
+                        key: &key,
+                    };
+                    // A caller only becomes the leader once, it returns below
+                    let fetch = fetch.take().unwrap();
+                    let result = fetch().await.map(Arc::new);
+                    if let Ok(body) = &result {
+                        self.remember(&key, body.clone());
+                    }
+                    sender.send_replace(Some(result.clone()));
+                    return result.map(|body| body.to_vec());
+                }
+                Role::Follower(mut receiver) => {
+                    let completed = receiver.wait_for(Option::is_some).await;
+                    if let Ok(result) = completed {
+                        let result = result.clone().unwrap();
+                        return result.map(|body| body.to_vec());
+                    }
+                    // The leader was cancelled, try again
+                }
+            }
+        }
+    }
+
+    fn recent(&self, key: &str) -> Option<Arc<Vec<u8>>> {
+        let mut recent = self.recent.lock().unwrap();
+        let index = recent.iter().position(|response| response.key == key)?;
+        let response = recent.remove(index)?;
+        if response.completed_at.elapsed() >= self.max_age {
+            return None;
+        }
+        let body = response.body.clone();
+        recent.push_front(response);
+        Some(body)
+    }
+
+    fn remember(&self, key: &str, body: Arc<Vec<u8>>) {
+        if self.capacity == 0 {
+            return;
+        }
+        let mut recent = self.recent.lock().unwrap();
+        recent.retain(|response| response.key != key);
+        recent.truncate(self.capacity - 1);
+        recent.push_front(RecentResponse {
+            key: key.to_string(),
+            completed_at: Instant::now(),
+            body,
+        });
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Coalescer;
+    use crate::client::ApiError;
+    use std::{
+        sync::atomic::{AtomicU32, Ordering},
+        time::Duration,
+    };
+
+    #[tokio::test]
+    async fn concurrent_requests_share_result() {
+        let coalescer = Coalescer::new(0, Duration::ZERO);
+        let fetches = AtomicU32::new(0);
+        let fetch = || async {
+            fetches.fetch_add(1, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            Ok(b"[]".to_vec())
+        };
+
+        let (first, second) = tokio::join!(
+            coalescer.run("/data/car/get".to_string(), fetch),
+            coalescer.run("/data/car/get".to_string(), fetch),
+        );
+        assert_eq!(first.unwrap(), b"[]");
+        assert_eq!(second.unwrap(), b"[]");
+        assert_eq!(fetches.load(Ordering::SeqCst), 1);
+
+        coalescer
+            .run("/data/car/get".to_string(), fetch)
+            .await
+            .unwrap();
+        assert_eq!(fetches.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn concurrent_requests_share_error() {
+        let coalescer = Coalescer::new(0, Duration::ZERO);
+        let fetch = || async {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            Err(ApiError::NotFound {
+                endpoint: "/data/results/get".to_string(),
+            })
+        };
+
+        let (first, second) = tokio::join!(
+            coalescer.run("/data/results/get".to_string(), fetch),
+            coalescer.run("/data/results/get".to_string(), fetch),
+        );
+        assert!(matches!(first, Err(ApiError::NotFound { .. })));
+        assert!(matches!(second, Err(ApiError::NotFound { .. })));
+    }
+
+    #[tokio::test]
+    async fn recent_responses_are_evicted() {
+        let coalescer = Coalescer::new(2, Duration::from_secs(60));
+        let fetches = AtomicU32::new(0);
+        let fetch = || async {
+            fetches.fetch_add(1, Ordering::SeqCst);
+            Ok(b"[]".to_vec())
+        };
+
+        for key in ["a", "b", "a", "c", "a", "b"] {
+            coalescer.run(key.to_string(), fetch).await.unwrap();
+        }
+        // "a" stays recent, "b" is evicted by "c"
+        assert_eq!(fetches.load(Ordering::SeqCst), 4);
+    }
+
+    #[tokio::test]
+    async fn recent_responses_expire() {
+        let coalescer = Coalescer::new(2, Duration::ZERO);
+        let fetches = AtomicU32::new(0);
+        let fetch = || async {
+            fetches.fetch_add(1, Ordering::SeqCst);
+            Ok(b"[]".to_vec())
+        };
+
+        coalescer.run("a".to_string(), fetch).await.unwrap();
+        coalescer.run("a".to_string(), fetch).await.unwrap();
+        assert_eq!(fetches.load(Ordering::SeqCst), 2);
+    }
+}
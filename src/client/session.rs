@@ -0,0 +1,388 @@
+use crate::model::auth::AuthSuccessBody;
+use std::{
+    fs,
+    io::{self, Write},
+    path::Path,
+    sync::Mutex,
+};
+use {
+    chrono::{DateTime, Duration, Utc},
+    reqwest::{
+        cookie::{CookieStore, Jar},
+        header::HeaderValue,
+        Url,
+    },
+    serde::{Deserialize, Serialize},
+    thiserror::Error,
+};
+
+/// An authenticated session, saved so that a client can be restored without
+/// logging in again
+///
+/// Create one with [IracingApiClient::save_session](super::IracingApiClient::save_session)
+/// and restore it with [IracingApiClientBuilder::session](super::IracingApiClientBuilder::session).
+/// The saved cookies grant access to the account, so store them as carefully
+/// as the password.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SavedSession {
+    /// The data returned by iRacing when the session was authenticated
+    pub auth: AuthSuccessBody,
+    /// The session cookies that had not expired when the session was saved
+    pub cookies: Vec<SavedCookie>,
+}
+
+impl SavedSession {
+    /// Serialize the session to a JSON string
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("A saved session is always valid JSON")
+    }
+
+    /// Deserialize a session previously serialized with [SavedSession::to_json]
+    pub fn from_json(json: &str) -> Result<Self, SessionFileError> {
+        serde_json::from_str(json).map_err(SessionFileError::Invalid)
+    }
+
+    /// Write the session to a file
+    ///
+    /// On Unix, the file is only readable and writable by its owner, since
+    /// the cookies grant access to the account.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), SessionFileError> {
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+
+        let write = || {
+            let mut file = options.open(path)?;
+            // The mode only applies to new files
+            #[cfg(unix)]
+            file.set_permissions(std::os::unix::fs::PermissionsExt::from_mode(0o600))?;
+            file.write_all(self.to_json().as_bytes())
+        };
+        write().map_err(SessionFileError::Io)
+    }
+
+    /// Read a session from a file written by [SavedSession::save]
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, SessionFileError> {
+        Self::from_json(&fs::read_to_string(path).map_err(SessionFileError::Io)?)
+    }
+
+    /// Whether every saved cookie has expired, in which case the session
+    /// cannot be restored
+    ///
+    /// A session without any cookies is expired as well, since requests
+    /// without cookies are never authenticated.
+    pub fn is_expired(&self) -> bool {
+        if self.cookies.is_empty() {
+            return true;
+        }
+        let now = Utc::now();
+        self.cookies.iter().all(|cookie| cookie.is_expired(now))
+    }
+}
+
+/// A session cookie, along with the attributes it was set with
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SavedCookie {
+    pub name: String,
+    pub value: String,
+    pub domain: Option<String>,
+    pub path: Option<String>,
+    /// `None` if the cookie only lasts as long as the session
+    pub expires: Option<DateTime<Utc>>,
+    pub secure: bool,
+}
+
+impl SavedCookie {
+    /// Parse the value of a `Set-Cookie` header received at `now`, converting a
+    /// `Max-Age` to an absolute expiry
+    pub(crate) fn parse(set_cookie: &str, now: DateTime<Utc>) -> Option<Self> {
+        let mut parts = set_cookie.split(';').map(str::trim);
+        let (name, value) = parts.next()?.split_once('=')?;
+        if name.trim().is_empty() {
+            return None;
+        }
+
+        let mut cookie = SavedCookie {
+            name: name.trim().to_string(),
+            value: value.trim().to_string(),
+            domain: None,
+            path: None,
+            expires: None,
+            secure: false,
+        };
+        let mut max_age = None;
+        for part in parts {
+            let (attribute, value) = part.split_once('=').unwrap_or((part, ""));
+            match attribute.trim().to_ascii_lowercase().as_str() {
+                "domain" => cookie.domain = Some(value.trim().to_string()),
+                "path" => cookie.path = Some(value.trim().to_string()),
+                // Dates may use the `01-Jan-2026` format of the original
+                // cookie specification
+                "expires" => {
+                    cookie.expires = DateTime::parse_from_rfc2822(&value.trim().replace('-', " "))
+                        .ok()
+                        .map(|expires| expires.with_timezone(&Utc))
+                }
+                "max-age" => max_age = value.trim().parse::<i64>().ok(),
+                "secure" => cookie.secure = true,
+                _ => {}
+            }
+        }
+        // Max-Age takes precedence over Expires. One too large to be
+        // represented never expires.
+        if let Some(max_age) = max_age {
+            cookie.expires =
+                Duration::try_seconds(max_age).and_then(|max_age| now.checked_add_signed(max_age));
+        }
+        Some(cookie)
+    }
+
+    pub fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        self.expires.is_some_and(|expires| expires <= now)
+    }
+
+    /// The cookie in the format of a `Set-Cookie` header
+    pub(crate) fn to_set_cookie(&self) -> String {
+        let mut set_cookie = format!("{}={}", self.name, self.value);
+        if let Some(domain) = &self.domain {
+            set_cookie.push_str(&format!("; Domain={}", domain));
+        }
+        if let Some(path) = &self.path {
+            set_cookie.push_str(&format!("; Path={}", path));
+        }
+        if let Some(expires) = self.expires {
+            set_cookie.push_str(
+                &expires
+                    .format("; Expires=%a, %d %b %Y %H:%M:%S GMT")
+                    .to_string(),
+            );
+        }
+        if self.secure {
+            set_cookie.push_str("; Secure");
+        }
+        set_cookie
+    }
+}
+
+/// The cookies of the current session
+///
+/// The cookies are kept in a [Jar], which decides which of them are sent with
+/// each request, and with their attributes, so that they can be saved.
+#[derive(Default)]
+pub(crate) struct SessionCookies {
+    jar: Jar,
+    saved: Mutex<Vec<SavedCookie>>,
+}
+
+impl SessionCookies {
+    /// The value of the `Cookie` header for a request to `url`
+    pub(crate) fn header(&self, url: &Url) -> Option<HeaderValue> {
+        self.jar.cookies(url)
+    }
+
+    /// Store the cookies set by a response from `url`
+    pub(crate) fn store<'a>(&self, set_cookies: impl Iterator<Item = &'a HeaderValue>, url: &Url) {
+        let now = Utc::now();
+        for set_cookie in set_cookies {
+            self.jar.set_cookies(&mut std::iter::once(set_cookie), url);
+            if let Some(cookie) = set_cookie
+                .to_str()
+                .ok()
+                .and_then(|set_cookie| SavedCookie::parse(set_cookie, now))
+            {
+                self.remember(cookie, now);
+            }
+        }
+    }
+
+    /// Restore the unexpired cookies of a saved session, as if they had been
+    /// set by a response from `url`
+    pub(crate) fn restore(&self, cookies: &[SavedCookie], url: &Url) {
+        let now = Utc::now();
+        for cookie in cookies.iter().filter(|cookie| !cookie.is_expired(now)) {
+            self.jar.add_cookie_str(&cookie.to_set_cookie(), url);
+            self.remember(cookie.clone(), now);
+        }
+    }
+
+    /// The cookies that have not expired yet
+    pub(crate) fn saved(&self) -> Vec<SavedCookie> {
+        let now = Utc::now();
+        let saved = self.saved.lock().unwrap();
+        saved
+            .iter()
+            .filter(|cookie| !cookie.is_expired(now))
+            .cloned()
+            .collect()
+    }
+
+    /// Replace the saved cookie with the same name, domain and path, or delete
+    /// it if the new cookie has already expired
+    fn remember(&self, cookie: SavedCookie, now: DateTime<Utc>) {
+        let mut saved = self.saved.lock().unwrap();
+        saved.retain(|saved| {
+            (&saved.name, &saved.domain, &saved.path)
+                != (&cookie.name, &cookie.domain, &cookie.path)
+        });
+        if !cookie.is_expired(now) {
+            saved.push(cookie);
+        }
+    }
+}
+
+/// The ways saving or loading a [SavedSession] can fail
+#[derive(Error, Debug)]
+pub enum SessionFileError {
+    #[error("Cannot access the session file")]
+    Io(#[source] io::Error),
+    #[error("The saved session is not valid")]
+    Invalid(#[source] serde_json::Error),
+}
+
+#[cfg(test)]
+mod test {
+    use super::{SavedCookie, SavedSession, SessionCookies, SessionFileError};
+    use chrono::{Duration, TimeZone, Utc};
+    use reqwest::{header::HeaderValue, Url};
+
+    fn cookie(name: &str, expires: Option<i64>) -> SavedCookie {
+        SavedCookie {
+            name: name.to_string(),
+            value: "value".to_string(),
+            domain: None,
+            path: Some("/".to_string()),
+            expires: expires.map(|timestamp| Utc.timestamp_opt(timestamp, 0).unwrap()),
+            secure: true,
+        }
+    }
+
+    fn session() -> SavedSession {
+        SavedSession {
+            auth: serde_json::from_str(include_str!("../../test_files/auth/success.json")).unwrap(),
+            cookies: vec![
+                cookie("irsso_membersv2", None),
+                cookie("authtoken_members", Some(1641600000)),
+            ],
+        }
+    }
+
+    #[test]
+    fn saved_session_json_round_trip() {
+        let session = session();
+        assert_eq!(
+            SavedSession::from_json(&session.to_json()).unwrap(),
+            session
+        );
+    }
+
+    #[test]
+    fn saved_session_invalid_json() {
+        assert!(matches!(
+            SavedSession::from_json("{}"),
+            Err(SessionFileError::Invalid(_))
+        ));
+    }
+
+    #[test]
+    fn saved_session_expired() {
+        let mut session = session();
+        assert!(!session.is_expired());
+        session.cookies.remove(0);
+        assert!(session.is_expired());
+        session.cookies.clear();
+        assert!(session.is_expired());
+    }
+
+    #[test]
+    fn saved_session_file_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("session.json");
+        std::fs::write(&path, "").unwrap();
+        let session = session();
+        session.save(&path).unwrap();
+        assert_eq!(SavedSession::load(&path).unwrap(), session);
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+    }
+
+    #[test]
+    fn saved_cookie_parse() {
+        let now = Utc.timestamp_opt(1641600000, 0).unwrap();
+        assert_eq!(
+            SavedCookie::parse(
+                "authtoken_members=%7B%22a%22%7D; Domain=.iracing.com; Path=/; Expires=Sat, 08-Jan-2022 12:00:00 GMT; Secure; HttpOnly",
+                now
+            ),
+            Some(SavedCookie {
+                name: "authtoken_members".to_string(),
+                value: "%7B%22a%22%7D".to_string(),
+                domain: Some(".iracing.com".to_string()),
+                path: Some("/".to_string()),
+                expires: Some(Utc.timestamp_opt(1641643200, 0).unwrap()),
+                secure: true,
+            })
+        );
+        assert_eq!(
+            SavedCookie::parse(
+                "a=b; Max-Age=60; Expires=Sat, 08 Jan 2022 12:00:00 GMT",
+                now
+            )
+            .unwrap()
+            .expires,
+            Some(now + Duration::seconds(60))
+        );
+        assert_eq!(
+            SavedCookie::parse("a=b; Max-Age=9223372036854775807", now)
+                .unwrap()
+                .expires,
+            None
+        );
+        assert_eq!(SavedCookie::parse("no value", now), None);
+    }
+
+    #[test]
+    fn saved_cookie_set_cookie_round_trip() {
+        let cookie = SavedCookie {
+            domain: Some(".iracing.com".to_string()),
+            ..cookie("authtoken_members", Some(1641600000))
+        };
+        assert_eq!(
+            SavedCookie::parse(&cookie.to_set_cookie(), Utc::now()),
+            Some(cookie)
+        );
+    }
+
+    #[test]
+    fn session_cookies_store_and_restore() {
+        let url = Url::parse("https://members-ng.iracing.com").unwrap();
+        let cookies = SessionCookies::default();
+        cookies.store(
+            [
+                HeaderValue::from_static("a=1; Path=/"),
+                HeaderValue::from_static("b=2; Path=/"),
+                HeaderValue::from_static("a=3; Path=/"),
+                HeaderValue::from_static("b=; Path=/; Max-Age=0"),
+            ]
+            .iter(),
+            &url,
+        );
+        assert_eq!(cookies.header(&url).unwrap(), "a=3");
+        let saved = cookies.saved();
+        assert_eq!(saved.len(), 1);
+
+        let restored = SessionCookies::default();
+        let expired = SavedCookie {
+            expires: Some(Utc::now() - Duration::seconds(1)),
+            ..cookie("c", None)
+        };
+        restored.restore(&[saved[0].clone(), expired], &url);
+        assert_eq!(restored.header(&url).unwrap(), "a=3");
+        assert_eq!(restored.saved(), saved);
+    }
+}
@@ -0,0 +1,197 @@
+//! Typed descriptions of iRacing's `/data` endpoints
+//!
+//! Every endpoint is described by a type implementing [Endpoint], and executed
+//! with [IracingApiClient::request](crate::IracingApiClient::request). Retries,
+//! re-authentication and rate limiting apply to every endpoint the same way.
+//!
+//! ```no_run
+//! # async fn example(client: iracing_api::IracingApiClient) -> Result<(), iracing_api::client::ApiError> {
+//! use iracing_api::model::season_results::{EventType, SeasonResultsQuery};
+//!
+//! let results = client
+//!     .request(&SeasonResultsQuery::new(3416).event_type(EventType::Race))
+//!     .await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{cache::CacheTtl, client::ApiError, model::chunk_info::ChunkInfo};
+use std::{collections::HashMap, sync::Arc};
+use {
+    chrono::{DateTime, Utc},
+    serde::de::DeserializeOwned,
+    thiserror::Error,
+};
+
+/// How an endpoint returns its data
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Envelope {
+    /// The response body is the data itself
+    Direct,
+    /// The response body is a `{ "link": ... }` object, pointing to the data
+    Link,
+}
+
+/// A request to one of iRacing's `/data` endpoints
+pub trait Endpoint {
+    /// The data returned by the endpoint
+    type Response: DeserializeOwned;
+
+    /// The path of the endpoint, e.g. `/data/car/get`
+    const PATH: &'static str;
+
+    /// How the endpoint returns its data
+    const ENVELOPE: Envelope = Envelope::Link;
+
+    /// The query parameters of the request
+    fn query(&self) -> Vec<(&'static str, String)> {
+        Vec::new()
+    }
+
+    /// Check the request against iRacing's rules before it is sent
+    fn validate(&self) -> Result<(), QueryError> {
+        Ok(())
+    }
+
+    /// How long the data can be served from a [ResponseCache](crate::cache::ResponseCache),
+    /// or `None` if it should not be cached
+    fn cache_ttl(&self) -> Option<CacheTtl> {
+        None
+    }
+
+    /// Deserialize the data returned by the endpoint
+    fn decode(&self, body: &[u8]) -> Result<Self::Response, ApiError> {
+        decode(Self::PATH, body)
+    }
+}
+
+/// An endpoint whose response describes data split into chunks, rather than
+/// containing the data itself
+///
+/// Executed with [IracingApiClient::request_chunked](crate::IracingApiClient::request_chunked)
+/// or [IracingApiClient::request_stream](crate::IracingApiClient::request_stream).
+/// Only the response is cached, not the chunks, and the chunk links it
+/// contains expire: chunked endpoints should not set a
+/// [cache_ttl](Endpoint::cache_ttl) longer than a few minutes.
+pub trait ChunkedEndpoint: Endpoint {
+    /// The type of each row in the chunks
+    type Row: DeserializeOwned;
+
+    /// The chunks described by the response, or `None` if there is no data
+    fn chunk_info(response: &Self::Response) -> Option<&ChunkInfo>;
+}
+
+/// The response of a [ChunkedEndpoint], along with the rows of all its chunks
+#[derive(Debug, Clone)]
+pub struct Chunked<R, T> {
+    pub response: R,
+    pub rows: Vec<T>,
+}
+
+/// A request breaks one of iRacing's rules, and would be rejected
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum QueryError {
+    #[error("Either a season or a time range is required")]
+    MissingTimeFrame,
+    #[error("The time range from {begin} to {end} is longer than 90 days")]
+    RangeTooLong {
+        begin: DateTime<Utc>,
+        end: DateTime<Utc>,
+    },
+    #[error("The time range begins at {begin}, more than 90 days ago, and needs an end")]
+    MissingRangeEnd { begin: DateTime<Utc> },
+    #[error("The time range ends at {end}, before it begins at {begin}")]
+    InvertedRange {
+        begin: DateTime<Utc>,
+        end: DateTime<Utc>,
+    },
+}
+
+/// Deserialize a response body, keeping track of where deserialization failed
+pub(crate) fn decode<T: DeserializeOwned>(endpoint: &str, body: &[u8]) -> Result<T, ApiError> {
+    let deserializer = &mut serde_json::Deserializer::from_slice(body);
+    serde_path_to_error::deserialize(deserializer).map_err(|err| ApiError::Deserialization {
+        endpoint: endpoint.to_string(),
+        path: err.path().to_string(),
+        source: Arc::new(err.into_inner()),
+    })
+}
+
+/// Decode a map of string IDs to asset data, as returned by the asset
+/// endpoints, to a `HashMap` keyed by numeric ID
+pub(crate) fn decode_asset_map<T: DeserializeOwned>(
+    endpoint: &str,
+    body: &[u8],
+) -> Result<HashMap<u32, T>, ApiError> {
+    let raw_data: serde_json::Map<String, serde_json::Value> = decode(endpoint, body)?;
+    raw_data
+        .into_iter()
+        .map(|(id, assets)| {
+            let invalid_assets = |path, source| ApiError::InvalidAssets {
+                endpoint: endpoint.to_string(),
+                id: id.clone(),
+                path,
+                source,
+            };
+            let parsed_id = id.parse().map_err(|_| invalid_assets(None, None))?;
+            let assets = serde_path_to_error::deserialize(assets).map_err(|err| {
+                invalid_assets(
+                    Some(err.path().to_string()),
+                    Some(Arc::new(err.into_inner())),
+                )
+            })?;
+            Ok((parsed_id, assets))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::{decode, decode_asset_map};
+    use crate::{
+        client::ApiError,
+        model::{car::CarAssets, car_class::CarClass},
+    };
+
+    #[test]
+    fn decode_reports_path() {
+        let body = br#"[{
+            "car_class_id": 1,
+            "cars_in_class": [{ "car_dirpath": "skipbarber", "car_id": "one", "retired": false }],
+            "cust_id": 0,
+            "name": "Skip Barber Formula 2000",
+            "relative_speed": 41,
+            "short_name": "Skip Barber Formula 2000"
+        }]"#;
+        match decode::<Vec<CarClass>>("/data/carclass/get", body) {
+            Err(ApiError::Deserialization { endpoint, path, .. }) => {
+                assert_eq!(endpoint, "/data/carclass/get");
+                assert_eq!(path, "[0].cars_in_class[0].car_id");
+            }
+            other => panic!("Expected a deserialization error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn decode_asset_map_invalid_id() {
+        match decode_asset_map::<CarAssets>("/data/car/assets", br#"{ "car": {} }"#) {
+            Err(ApiError::InvalidAssets { id, path, .. }) => {
+                assert_eq!(id, "car");
+                assert_eq!(path, None);
+            }
+            other => panic!("Expected an asset error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn decode_asset_map_invalid_assets() {
+        match decode_asset_map::<CarAssets>("/data/car/assets", br#"{ "1": { "car_id": "one" } }"#)
+        {
+            Err(ApiError::InvalidAssets { id, path, .. }) => {
+                assert_eq!(id, "1");
+                assert_eq!(path.as_deref(), Some("car_id"));
+            }
+            other => panic!("Expected an asset error, got {:?}", other),
+        }
+    }
+}
@@ -1,4 +1,9 @@
+pub mod cache;
 pub mod client;
+pub mod endpoint;
 pub mod model;
+pub mod rate_limit;
+pub mod retry;
+pub mod transport;
 
-pub use client::IracingApiClient;
+pub use client::{IracingApiClient, IracingApiClientBuilder};
@@ -1,7 +1,9 @@
 use std::fmt;
 
 use {
+    base64::{engine::general_purpose::STANDARD as BASE64, Engine},
     serde::{Deserialize, Serialize},
+    sha2::{Digest, Sha256},
     thiserror::Error,
 };
 
@@ -9,9 +11,37 @@ use {
 #[derive(Serialize, Debug, Clone)]
 pub struct AuthRequestBody {
     pub email: String,
+    /// The password, hashed with [hash_password]. iRacing rejects plain
+    /// passwords.
     pub password: String,
 }
 
+impl AuthRequestBody {
+    /// Create credentials from an email and plain password, hashing the
+    /// password
+    pub fn new(email: &str, password: &str) -> Self {
+        Self::with_hashed_password(email, &hash_password(email, password))
+    }
+
+    /// Create credentials from an email and a password already hashed with
+    /// [hash_password], so that the plain password doesn't need to be stored
+    pub fn with_hashed_password(email: &str, hashed_password: &str) -> Self {
+        Self {
+            email: email.to_string(),
+            password: hashed_password.to_string(),
+        }
+    }
+}
+
+/// Hash a password the way iRacing expects it to be sent:
+/// `base64(sha256(password + lowercase(email)))`
+pub fn hash_password(email: &str, password: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(password);
+    hasher.update(email.to_lowercase());
+    BASE64.encode(hasher.finalize())
+}
+
 /// The response returned by the authentication endpoint when authentication is
 /// successful for not
 #[derive(Debug, Clone, PartialEq)]
@@ -23,32 +53,43 @@ pub enum AuthResponse {
 impl AuthResponse {
     /// Interpret a raw json value as either an [AuthSuccessBody] or [AuthFailureBody]
     ///
-    /// # Panics
+    /// # Errors
     ///
-    /// This function panics if the response structure doesn't match what iRacing usually returns
-    pub fn from_json(value: serde_json::Value) -> Self {
+    /// This function fails if the response structure doesn't match what iRacing usually returns
+    pub fn from_json(value: serde_json::Value) -> Result<Self, AuthResponseError> {
         match value
             .get("authcode")
-            .expect("Field \"authcode\" missing from response body")
+            .ok_or(AuthResponseError::MissingAuthcode)?
         {
-            serde_json::Value::String(_) => Self::Success(
-                serde_json::from_value(value)
-                    .expect("Could not interpret json value as AuthSuccessBody"),
-            ),
-            serde_json::Value::Number(_) => Self::Failure(
-                serde_json::from_value(value)
-                    .expect("Could not interpret json value as AuthSuccessBody"),
-            ),
-            invalid_authcode => panic!(
-                "\"authcode\" is not a `String` or `Number`; actual value: {:?}",
-                invalid_authcode
-            ),
+            serde_json::Value::String(_) => serde_json::from_value(value)
+                .map(Self::Success)
+                .map_err(AuthResponseError::InvalidSuccessBody),
+            serde_json::Value::Number(_) => serde_json::from_value(value)
+                .map(Self::Failure)
+                .map_err(AuthResponseError::InvalidFailureBody),
+            invalid_authcode => Err(AuthResponseError::InvalidAuthcode(invalid_authcode.clone())),
         }
     }
 }
 
+/// The ways the authentication endpoint's response can fail to match what
+/// iRacing usually returns. This should be considered a bug.
+#[derive(Error, Debug)]
+pub enum AuthResponseError {
+    #[error("Response body is not valid JSON")]
+    NotJson(#[source] serde_json::Error),
+    #[error("Field \"authcode\" missing from response body")]
+    MissingAuthcode,
+    #[error("\"authcode\" is not a `String` or `Number`; actual value: {0:?}")]
+    InvalidAuthcode(serde_json::Value),
+    #[error("Could not interpret json value as AuthSuccessBody")]
+    InvalidSuccessBody(#[source] serde_json::Error),
+    #[error("Could not interpret json value as AuthFailureBody")]
+    InvalidFailureBody(#[source] serde_json::Error),
+}
+
 /// The response body returned by iRacing when authenciation succeeds
-#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct AuthSuccessBody {
     #[serde(rename = "authcode")]
     pub auth_code: String,
@@ -129,14 +170,44 @@ impl fmt::Display for AuthError {
 
 #[cfg(test)]
 mod test {
-    use super::AuthResponse;
+    use super::{hash_password, AuthRequestBody, AuthResponse, AuthResponseError};
+
+    #[test]
+    fn hash_password_known_vectors() {
+        // The example from iRacing's authentication documentation
+        assert_eq!(
+            hash_password("CLunky@iracing.Com", "MyPassWord"),
+            "xGKecAR27ALXNuMLsGaG0v5Q9pSs2tZTZRKNgmHMg+Q="
+        );
+        assert_eq!(
+            hash_password("email@example.com", "password"),
+            "D0OVE01PFmrT2kHcvwRzTNY3wFbYHnbTD3DoJi3OcSI="
+        );
+    }
+
+    #[test]
+    fn auth_request_body_hashes_password() {
+        let body = AuthRequestBody::new("CLunky@iracing.Com", "MyPassWord");
+        assert_eq!(body.email, "CLunky@iracing.Com");
+        assert_eq!(
+            body.password,
+            "xGKecAR27ALXNuMLsGaG0v5Q9pSs2tZTZRKNgmHMg+Q="
+        );
+
+        let body = AuthRequestBody::with_hashed_password("CLunky@iracing.Com", &body.password);
+        assert_eq!(
+            body.password,
+            "xGKecAR27ALXNuMLsGaG0v5Q9pSs2tZTZRKNgmHMg+Q="
+        );
+    }
+
     #[test]
     fn auth_response_from_json_success() {
         let json_value: serde_json::Value =
             serde_json::from_str(include_str!("../../test_files/auth/success.json")).unwrap();
         assert!(matches!(
             AuthResponse::from_json(json_value),
-            AuthResponse::Success(_),
+            Ok(AuthResponse::Success(_)),
         ));
     }
 
@@ -151,21 +222,37 @@ mod test {
         let json_value: serde_json::Value = serde_json::from_str(raw_json).unwrap();
         assert!(matches!(
             AuthResponse::from_json(json_value),
-            AuthResponse::Failure(_),
+            Ok(AuthResponse::Failure(_)),
         ));
     }
 
     #[test]
-    #[should_panic]
     fn auth_response_from_json_missing_authcode() {
         let json_value: serde_json::Value = serde_json::from_str("{}").unwrap();
-        AuthResponse::from_json(json_value);
+        assert!(matches!(
+            AuthResponse::from_json(json_value),
+            Err(AuthResponseError::MissingAuthcode),
+        ));
     }
 
     #[test]
-    #[should_panic]
     fn auth_response_from_json_invalid_authcode() {
         let json_value: serde_json::Value = serde_json::from_str("{ \"authcode\": [] }").unwrap();
-        AuthResponse::from_json(json_value);
+        assert!(matches!(
+            AuthResponse::from_json(json_value),
+            Err(AuthResponseError::InvalidAuthcode(
+                serde_json::Value::Array(_)
+            )),
+        ));
+    }
+
+    #[test]
+    fn auth_response_from_json_invalid_success_body() {
+        let json_value: serde_json::Value =
+            serde_json::from_str("{ \"authcode\": \"authcode\" }").unwrap();
+        assert!(matches!(
+            AuthResponse::from_json(json_value),
+            Err(AuthResponseError::InvalidSuccessBody(_)),
+        ));
     }
 }
@@ -1,9 +1,44 @@
-use std::path::PathBuf;
+use crate::{
+    cache::CacheTtl,
+    client::ApiError,
+    endpoint::{decode_asset_map, Endpoint},
+};
+use std::{collections::HashMap, path::PathBuf};
 use {
     chrono::{DateTime, Utc},
     serde::{Deserialize, Serialize},
 };
 
+/// Request for every car, from `/data/car/get`
+pub struct CarsQuery;
+
+impl Endpoint for CarsQuery {
+    type Response = Vec<Car>;
+    const PATH: &'static str = "/data/car/get";
+
+    fn cache_ttl(&self) -> Option<CacheTtl> {
+        Some(CacheTtl::DAY)
+    }
+}
+
+/// Request for the assets of every car, from `/data/car/assets`
+///
+/// The response maps car IDs to asset data, like the actual API.
+pub struct CarAssetsQuery;
+
+impl Endpoint for CarAssetsQuery {
+    type Response = HashMap<u32, CarAssets>;
+    const PATH: &'static str = "/data/car/assets";
+
+    fn cache_ttl(&self) -> Option<CacheTtl> {
+        Some(CacheTtl::DAY)
+    }
+
+    fn decode(&self, body: &[u8]) -> Result<Self::Response, ApiError> {
+        decode_asset_map(Self::PATH, body)
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Car {
     pub ai_enabled: bool,
@@ -0,0 +1,61 @@
+use crate::{cache::CacheTtl, endpoint::Endpoint};
+use serde::{Deserialize, Serialize};
+
+/// Request for every car class, from `/data/carclass/get`
+pub struct CarClassesQuery;
+
+impl Endpoint for CarClassesQuery {
+    type Response = Vec<CarClass>;
+    const PATH: &'static str = "/data/carclass/get";
+
+    fn cache_ttl(&self) -> Option<CacheTtl> {
+        Some(CacheTtl::DAY)
+    }
+}
+
+/// A car class, as returned by `/data/carclass/get`
+///
+/// The `car_class_id` matches the one found in
+/// [`SubsessionDriverResult`](super::results::SubsessionDriverResult).
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CarClass {
+    pub car_class_id: u32,
+    pub cars_in_class: Vec<CarInClass>,
+    /// Appears to always be `0` for official classes
+    pub cust_id: u32,
+    pub name: String,
+    /// Omitted by iRacing for some classes
+    #[serde(default)]
+    pub rain_enabled: bool,
+    pub relative_speed: u32,
+    pub short_name: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CarInClass {
+    pub car_dirpath: String,
+    pub car_id: u32,
+    #[serde(default)]
+    pub rain_enabled: bool,
+    pub retired: bool,
+}
+
+#[cfg(test)]
+mod test {
+    use super::CarClass;
+
+    #[test]
+    fn car_class_deserialize() {
+        let classes: Vec<CarClass> =
+            serde_json::from_str(include_str!("../../test_files/car_class/get.json")).unwrap();
+        assert_eq!(classes.len(), 2);
+
+        let mx5 = &classes[1];
+        assert_eq!(mx5.car_class_id, 4018);
+        assert_eq!(mx5.short_name, "MX-5 Cup");
+        assert!(!mx5.rain_enabled);
+        assert_eq!(mx5.cars_in_class.len(), 2);
+        assert!(mx5.cars_in_class[0].rain_enabled);
+        assert!(mx5.cars_in_class[1].retired);
+    }
+}
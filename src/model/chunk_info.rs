@@ -0,0 +1,55 @@
+use serde::{Deserialize, Serialize};
+
+/// Describes data split into chunk files, returned by endpoints with large
+/// result sets instead of the data itself
+///
+/// Each chunk file is a JSON array of rows. The chunks can be fetched with
+/// [IracingApiClient::fetch_chunks](crate::IracingApiClient::fetch_chunks) or
+/// [IracingApiClient::chunk_stream](crate::IracingApiClient::chunk_stream).
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ChunkInfo {
+    /// The maximum number of rows per chunk
+    pub chunk_size: u32,
+    pub num_chunks: u32,
+    /// The total number of rows in all chunks
+    pub rows: u32,
+    pub base_download_url: String,
+    /// Relative to `base_download_url`, in order
+    #[serde(default)]
+    pub chunk_file_names: Vec<String>,
+}
+
+impl ChunkInfo {
+    /// The URLs of the chunk files, in order
+    pub fn chunk_urls(&self) -> impl Iterator<Item = String> + '_ {
+        self.chunk_file_names
+            .iter()
+            .map(move |file_name| format!("{}{}", self.base_download_url, file_name))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::ChunkInfo;
+
+    #[test]
+    fn chunk_info_urls() {
+        let chunk_info: ChunkInfo = serde_json::from_str(
+            r#"{
+                "chunk_size": 500,
+                "num_chunks": 2,
+                "rows": 612,
+                "base_download_url": "https://scorpio-assets.s3.amazonaws.com/production/data-server/migrated/results/",
+                "chunk_file_names": ["abc_0.json", "abc_1.json"]
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            chunk_info.chunk_urls().collect::<Vec<_>>(),
+            [
+                "https://scorpio-assets.s3.amazonaws.com/production/data-server/migrated/results/abc_0.json",
+                "https://scorpio-assets.s3.amazonaws.com/production/data-server/migrated/results/abc_1.json",
+            ]
+        );
+    }
+}
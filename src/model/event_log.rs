@@ -0,0 +1,159 @@
+use super::chunk_info::ChunkInfo;
+use crate::endpoint::{ChunkedEndpoint, Endpoint};
+use serde::{Deserialize, Serialize};
+
+/// Request for the events of a session, from `/data/results/event_log`
+pub struct EventLogQuery {
+    subsession_id: u32,
+    simsession_number: i32,
+}
+
+impl EventLogQuery {
+    /// `simsession_number` is `0` for the main event, `-1` for the preceding
+    /// session, and so on
+    pub fn new(subsession_id: u32, simsession_number: i32) -> Self {
+        Self {
+            subsession_id,
+            simsession_number,
+        }
+    }
+}
+
+impl Endpoint for EventLogQuery {
+    type Response = EventLog;
+    const PATH: &'static str = "/data/results/event_log";
+
+    fn query(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("subsession_id", self.subsession_id.to_string()),
+            ("simsession_number", self.simsession_number.to_string()),
+        ]
+    }
+}
+
+impl ChunkedEndpoint for EventLogQuery {
+    type Row = EventLogEntry;
+
+    fn chunk_info(response: &EventLog) -> Option<&ChunkInfo> {
+        response.chunk_info.as_ref()
+    }
+}
+
+/// The response of `/data/results/event_log`. The events themselves are
+/// split into chunks.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct EventLog {
+    pub success: bool,
+    /// `None` if there are no events
+    pub chunk_info: Option<ChunkInfo>,
+}
+
+/// One event of a session, in a chunk of `/data/results/event_log`
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct EventLogEntry {
+    pub subsession_id: u32,
+    pub simsession_number: i32,
+    /// Time since the start of the session, in ten-thousandths of a second
+    pub session_time: i64,
+    /// The position of the event in the log
+    pub event_seq: u32,
+    /// What happened, as a number. The meaning of the codes is not
+    /// documented, use [event](Self::event) instead.
+    pub event_code: u32,
+    /// The `cust_id` of the driver, or the `team_id` of the team, or `-1`
+    /// for events not related to an entrant
+    pub group_id: i32,
+    /// `-1` for events not related to a driver
+    pub cust_id: i32,
+    pub display_name: String,
+    pub lap_number: i32,
+    /// A summary of the event, e.g. `4x incident`
+    pub description: String,
+    /// The text of chat and admin messages
+    pub message: String,
+}
+
+impl EventLogEntry {
+    /// What happened, recognised from the [description](Self::description).
+    /// The meaning of `event_code` is not documented, so events with a
+    /// description not known to this crate are [LogEvent::Other].
+    pub fn event(&self) -> LogEvent {
+        let description = self.description.to_lowercase();
+        match description.as_str() {
+            "caution" => LogEvent::Caution,
+            "green flag" => LogEvent::Green,
+            "pitted" => LogEvent::Pitted,
+            "driver change" => LogEvent::DriverChange,
+            "chat" => LogEvent::Chat,
+            "admin message" => LogEvent::AdminMessage,
+            "disqualified" => LogEvent::Disqualification,
+            description if description.ends_with("x incident") => LogEvent::Incident,
+            _ => LogEvent::Other(self.event_code),
+        }
+    }
+}
+
+/// What happened, as returned by [EventLogEntry::event]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LogEvent {
+    Caution,
+    Green,
+    Incident,
+    Pitted,
+    DriverChange,
+    Chat,
+    AdminMessage,
+    Disqualification,
+    /// An event not known to this crate, with its `event_code`
+    Other(u32),
+}
+
+#[cfg(test)]
+mod test {
+    use super::{EventLog, EventLogEntry, LogEvent};
+
+    #[test]
+    fn event_log_deserialize() {
+        let log: EventLog =
+            serde_json::from_str(include_str!("../../test_files/event_log/event_log.json"))
+                .unwrap();
+        assert_eq!(log.chunk_info.unwrap().rows, 5);
+    }
+
+    #[test]
+    fn event_log_entries_deserialize() {
+        let entries: Vec<EventLogEntry> =
+            serde_json::from_str(include_str!("../../test_files/event_log/chunk.json")).unwrap();
+        let events: Vec<_> = entries.iter().map(EventLogEntry::event).collect();
+        assert_eq!(
+            events,
+            [
+                LogEvent::Incident,
+                LogEvent::Caution,
+                LogEvent::Pitted,
+                LogEvent::Green,
+                LogEvent::Other(42)
+            ]
+        );
+        assert_eq!(entries[0].description, "4x incident");
+        assert_eq!(entries[4].event_code, 42);
+    }
+
+    #[test]
+    fn log_event_from_description() {
+        let mut entries: Vec<EventLogEntry> =
+            serde_json::from_str(include_str!("../../test_files/event_log/chunk.json")).unwrap();
+        let entry = &mut entries[0];
+        for (description, event) in [
+            ("Driver change", LogEvent::DriverChange),
+            ("Chat", LogEvent::Chat),
+            ("Admin message", LogEvent::AdminMessage),
+            ("Disqualified", LogEvent::Disqualification),
+            ("0x incident", LogEvent::Incident),
+            ("Incident", LogEvent::Other(3)),
+        ] {
+            entry.description = description.to_string();
+            assert_eq!(entry.event(), event);
+        }
+    }
+}
@@ -0,0 +1,154 @@
+use super::{chunk_info::ChunkInfo, lap_data::Lap};
+use crate::endpoint::{ChunkedEndpoint, Endpoint};
+use std::collections::BTreeMap;
+use {
+    chrono::{DateTime, Utc},
+    serde::{Deserialize, Serialize},
+};
+
+/// Request for the laps of every driver in a session, from
+/// `/data/results/lap_chart_data`
+pub struct LapChartQuery {
+    subsession_id: u32,
+    simsession_number: i32,
+}
+
+impl LapChartQuery {
+    /// `simsession_number` is `0` for the main event, `-1` for the preceding
+    /// session, and so on
+    pub fn new(subsession_id: u32, simsession_number: i32) -> Self {
+        Self {
+            subsession_id,
+            simsession_number,
+        }
+    }
+}
+
+impl Endpoint for LapChartQuery {
+    type Response = LapChartData;
+    const PATH: &'static str = "/data/results/lap_chart_data";
+
+    fn query(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("subsession_id", self.subsession_id.to_string()),
+            ("simsession_number", self.simsession_number.to_string()),
+        ]
+    }
+}
+
+impl ChunkedEndpoint for LapChartQuery {
+    type Row = Lap;
+
+    fn chunk_info(response: &LapChartData) -> Option<&ChunkInfo> {
+        response.chunk_info.as_ref()
+    }
+}
+
+/// Summary of the laps of a session, as returned by
+/// `/data/results/lap_chart_data`. The laps themselves are split into chunks.
+///
+/// Times are in ten-thousandths of a second, and `-1` if not set.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct LapChartData {
+    pub success: bool,
+    pub best_lap_num: i32,
+    pub best_lap_time: i32,
+    pub best_nlaps_num: i32,
+    pub best_nlaps_time: i32,
+    pub best_qual_lap_num: i32,
+    pub best_qual_lap_time: i32,
+    pub best_qual_lap_at: Option<DateTime<Utc>>,
+    pub last_updated: Option<DateTime<Utc>>,
+    /// `None` if there are no laps
+    pub chunk_info: Option<ChunkInfo>,
+}
+
+/// The position of a driver at the end of a lap
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LapPosition {
+    pub lap_number: u32,
+    pub position: u32,
+}
+
+/// Pivot the laps of a lap chart into the positions of each driver, keyed
+/// by `cust_id` and ordered by lap
+pub fn positions_by_driver(laps: &[Lap]) -> BTreeMap<u32, Vec<LapPosition>> {
+    let mut drivers: BTreeMap<u32, Vec<LapPosition>> = BTreeMap::new();
+    for lap in laps {
+        drivers.entry(lap.cust_id).or_default().push(LapPosition {
+            lap_number: lap.lap_number,
+            position: lap.lap_position,
+        });
+    }
+    for positions in drivers.values_mut() {
+        positions.sort_by_key(|position| position.lap_number);
+    }
+    drivers
+}
+
+/// Pivot the laps of a lap chart into the running order at the end of each
+/// lap, as `cust_id`s from the leader backwards
+pub fn running_order(laps: &[Lap]) -> BTreeMap<u32, Vec<u32>> {
+    let mut order: BTreeMap<u32, Vec<&Lap>> = BTreeMap::new();
+    for lap in laps {
+        order.entry(lap.lap_number).or_default().push(lap);
+    }
+    order
+        .into_iter()
+        .map(|(lap_number, mut laps)| {
+            laps.sort_by_key(|lap| lap.lap_position);
+            (lap_number, laps.iter().map(|lap| lap.cust_id).collect())
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::{positions_by_driver, running_order, LapChartData, LapPosition};
+    use crate::model::lap_data::Lap;
+
+    fn laps() -> Vec<Lap> {
+        serde_json::from_str(include_str!("../../test_files/lap_chart/chunk.json")).unwrap()
+    }
+
+    #[test]
+    fn lap_chart_data_deserialize() {
+        let data: LapChartData = serde_json::from_str(include_str!(
+            "../../test_files/lap_chart/lap_chart_data.json"
+        ))
+        .unwrap();
+        assert_eq!(data.chunk_info.unwrap().rows, 6);
+    }
+
+    #[test]
+    fn pivot_positions_by_driver() {
+        let drivers = positions_by_driver(&laps());
+        assert_eq!(drivers.len(), 2);
+        assert_eq!(
+            drivers[&123456],
+            [
+                LapPosition {
+                    lap_number: 0,
+                    position: 2
+                },
+                LapPosition {
+                    lap_number: 1,
+                    position: 1
+                },
+                LapPosition {
+                    lap_number: 2,
+                    position: 1
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn pivot_running_order() {
+        let order = running_order(&laps());
+        assert_eq!(order.len(), 3);
+        assert_eq!(order[&0], [234567, 123456]);
+        assert_eq!(order[&1], [123456, 234567]);
+        assert_eq!(order[&2], [123456, 234567]);
+    }
+}
@@ -0,0 +1,257 @@
+use super::{chunk_info::ChunkInfo, results::Helmet};
+use crate::endpoint::{ChunkedEndpoint, Endpoint};
+use std::{fmt, time::Duration};
+use {
+    chrono::{DateTime, Utc},
+    serde::{Deserialize, Serialize},
+};
+
+/// Request for every lap of a driver or team in a session, from
+/// `/data/results/lap_data`
+pub struct LapDataQuery {
+    subsession_id: u32,
+    simsession_number: i32,
+    entrant: Entrant,
+}
+
+/// Whose laps to request. In team events, a team's laps include those of
+/// every driver of the team.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Entrant {
+    /// A driver, by `cust_id`
+    Driver(u32),
+    /// A team, by `team_id`
+    Team(u32),
+}
+
+impl LapDataQuery {
+    /// `simsession_number` is `0` for the main event, `-1` for the preceding
+    /// session, and so on, like [SubsessionResult::simsession_number](super::results::SubsessionResult::simsession_number)
+    pub fn new(subsession_id: u32, simsession_number: i32, entrant: Entrant) -> Self {
+        Self {
+            subsession_id,
+            simsession_number,
+            entrant,
+        }
+    }
+}
+
+impl Endpoint for LapDataQuery {
+    type Response = LapData;
+    const PATH: &'static str = "/data/results/lap_data";
+
+    fn query(&self) -> Vec<(&'static str, String)> {
+        let entrant = match self.entrant {
+            Entrant::Driver(cust_id) => ("cust_id", cust_id.to_string()),
+            Entrant::Team(team_id) => ("team_id", team_id.to_string()),
+        };
+        vec![
+            ("subsession_id", self.subsession_id.to_string()),
+            ("simsession_number", self.simsession_number.to_string()),
+            entrant,
+        ]
+    }
+}
+
+impl ChunkedEndpoint for LapDataQuery {
+    type Row = Lap;
+
+    fn chunk_info(response: &LapData) -> Option<&ChunkInfo> {
+        response.chunk_info.as_ref()
+    }
+}
+
+/// Summary of the laps of a driver or team, as returned by
+/// `/data/results/lap_data`. The laps themselves are split into chunks.
+///
+/// Times are in ten-thousandths of a second, and `-1` if not set.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct LapData {
+    pub success: bool,
+    /// The `cust_id` of the driver, or the `team_id` of the team
+    pub group_id: i32,
+    pub name: String,
+    pub cust_id: Option<u32>,
+    pub car_id: u32,
+    pub best_lap_num: i32,
+    pub best_lap_time: i32,
+    pub best_nlaps_num: i32,
+    pub best_nlaps_time: i32,
+    pub best_qual_lap_num: i32,
+    pub best_qual_lap_time: i32,
+    pub best_qual_lap_at: Option<DateTime<Utc>>,
+    pub last_updated: Option<DateTime<Utc>>,
+    /// `None` if there are no laps
+    pub chunk_info: Option<ChunkInfo>,
+}
+
+/// One lap of a driver, in a chunk of `/data/results/lap_data`
+///
+/// Times are in ten-thousandths of a second.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct Lap {
+    /// The `cust_id` of the driver, or the `team_id` of the team
+    pub group_id: i32,
+    pub name: String,
+    pub cust_id: u32,
+    pub display_name: String,
+    pub lap_number: u32,
+    /// What happened during the lap, as a bitfield. The meaning of the bits
+    /// is not documented, use [lap_events](Self::lap_events) instead.
+    pub flags: u32,
+    pub incident: bool,
+    /// Time since the start of the session when the lap was completed
+    pub session_time: i64,
+    /// `-1` if the lap has no valid time, e.g. the first lap of a rolling
+    /// start
+    pub lap_time: i32,
+    pub lap_position: u32,
+    pub interval: Option<i32>,
+    pub interval_units: Option<String>,
+    #[serde(default)]
+    pub lap_events: Vec<LapEvent>,
+    #[serde(default)]
+    pub personal_best_lap: bool,
+    #[serde(default)]
+    pub team_fastest_lap: bool,
+    #[serde(default)]
+    pub fastest_lap: bool,
+    pub car_number: Option<String>,
+    pub license_level: Option<u32>,
+    pub helmet: Option<Helmet>,
+    #[serde(default)]
+    pub ai: bool,
+}
+
+impl Lap {
+    /// The lap time, or `None` if the lap has no valid time
+    pub fn duration(&self) -> Option<Duration> {
+        u64::try_from(self.lap_time)
+            .ok()
+            .map(|lap_time| Duration::from_micros(lap_time * 100))
+    }
+}
+
+/// An event during a lap, as listed in [Lap::lap_events]
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq, Eq)]
+#[serde(from = "String", into = "String")]
+pub enum LapEvent {
+    Invalid,
+    Pitted,
+    OffTrack,
+    BlackFlag,
+    CarReset,
+    Contact,
+    CarContact,
+    LostControl,
+    Discontinuity,
+    InterpolatedCrossing,
+    ClockSmash,
+    Tow,
+    /// An event not known to this crate
+    Other(String),
+}
+
+impl From<String> for LapEvent {
+    fn from(event: String) -> Self {
+        match event.as_str() {
+            "invalid" => LapEvent::Invalid,
+            "pitted" => LapEvent::Pitted,
+            "off track" => LapEvent::OffTrack,
+            "black flag" => LapEvent::BlackFlag,
+            "car reset" => LapEvent::CarReset,
+            "contact" => LapEvent::Contact,
+            "car contact" => LapEvent::CarContact,
+            "lost control" => LapEvent::LostControl,
+            "discontinuity" => LapEvent::Discontinuity,
+            "interpolated crossing" => LapEvent::InterpolatedCrossing,
+            "clock smash" => LapEvent::ClockSmash,
+            "tow" => LapEvent::Tow,
+            _ => LapEvent::Other(event),
+        }
+    }
+}
+
+impl From<LapEvent> for String {
+    fn from(event: LapEvent) -> Self {
+        event.to_string()
+    }
+}
+
+impl fmt::Display for LapEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let event = match self {
+            LapEvent::Invalid => "invalid",
+            LapEvent::Pitted => "pitted",
+            LapEvent::OffTrack => "off track",
+            LapEvent::BlackFlag => "black flag",
+            LapEvent::CarReset => "car reset",
+            LapEvent::Contact => "contact",
+            LapEvent::CarContact => "car contact",
+            LapEvent::LostControl => "lost control",
+            LapEvent::Discontinuity => "discontinuity",
+            LapEvent::InterpolatedCrossing => "interpolated crossing",
+            LapEvent::ClockSmash => "clock smash",
+            LapEvent::Tow => "tow",
+            LapEvent::Other(event) => event,
+        };
+        write!(f, "{}", event)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Entrant, Lap, LapData, LapDataQuery, LapEvent};
+    use crate::endpoint::Endpoint;
+    use std::time::Duration;
+
+    #[test]
+    fn lap_data_deserialize() {
+        let lap_data: LapData =
+            serde_json::from_str(include_str!("../../test_files/lap_data/lap_data.json")).unwrap();
+        assert_eq!(lap_data.best_lap_num, 1);
+        assert_eq!(lap_data.chunk_info.unwrap().chunk_file_names.len(), 1);
+    }
+
+    #[test]
+    fn laps_deserialize() {
+        let laps: Vec<Lap> =
+            serde_json::from_str(include_str!("../../test_files/lap_data/chunk.json")).unwrap();
+        assert_eq!(laps.len(), 3);
+        assert_eq!(laps[0].duration(), None);
+        assert_eq!(laps[1].duration(), Some(Duration::from_millis(59_340)));
+
+        let pitted = &laps[2];
+        assert_eq!(pitted.flags, 6);
+        assert_eq!(
+            pitted.lap_events,
+            [
+                LapEvent::Pitted,
+                LapEvent::OffTrack,
+                LapEvent::Other("new event".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn lap_event_round_trip() {
+        let events = serde_json::json!(["off track", "new event"]);
+        let parsed: Vec<LapEvent> = serde_json::from_value(events.clone()).unwrap();
+        assert_eq!(serde_json::to_value(parsed).unwrap(), events);
+    }
+
+    #[test]
+    fn lap_data_query_params() {
+        let query = LapDataQuery::new(38280997, 0, Entrant::Team(1234)).query();
+        assert_eq!(query[2], ("team_id", "1234".to_string()));
+        let query = LapDataQuery::new(38280997, 0, Entrant::Driver(123456)).query();
+        assert_eq!(
+            query,
+            [
+                ("subsession_id", "38280997".to_string()),
+                ("simsession_number", "0".to_string()),
+                ("cust_id", "123456".to_string())
+            ]
+        );
+    }
+}
@@ -0,0 +1,79 @@
+use super::results::Helmet;
+use crate::endpoint::Endpoint;
+use serde::{Deserialize, Serialize};
+
+/// Request for the drivers whose name or ID matches a search term, from
+/// `/data/lookup/drivers`
+pub struct DriversQuery {
+    search_term: String,
+    league_id: Option<u32>,
+}
+
+impl DriversQuery {
+    pub fn new(search_term: &str) -> Self {
+        Self {
+            search_term: search_term.to_string(),
+            league_id: None,
+        }
+    }
+
+    /// Only search the members of a league
+    pub fn league_id(mut self, league_id: u32) -> Self {
+        self.league_id = Some(league_id);
+        self
+    }
+}
+
+impl Endpoint for DriversQuery {
+    type Response = Vec<DriverMatch>;
+    const PATH: &'static str = "/data/lookup/drivers";
+
+    fn query(&self) -> Vec<(&'static str, String)> {
+        let mut query = vec![("search_term", self.search_term.clone())];
+        if let Some(league_id) = self.league_id {
+            query.push(("league_id", league_id.to_string()));
+        }
+        query
+    }
+}
+
+/// A driver matching a [DriversQuery]
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct DriverMatch {
+    pub cust_id: u32,
+    pub display_name: String,
+    pub helmet: Option<Helmet>,
+    #[serde(default)]
+    pub profile_disabled: bool,
+}
+
+#[cfg(test)]
+mod test {
+    use super::{DriverMatch, DriversQuery};
+    use crate::endpoint::Endpoint;
+
+    #[test]
+    fn driver_match_deserialize() {
+        let drivers: Vec<DriverMatch> =
+            serde_json::from_str(include_str!("../../test_files/lookup/drivers.json")).unwrap();
+        assert_eq!(drivers.len(), 2);
+        assert_eq!(drivers[0].cust_id, 123456);
+        assert_eq!(drivers[0].display_name, "Test Driver");
+        assert!(drivers[1].profile_disabled);
+    }
+
+    #[test]
+    fn drivers_query_params() {
+        assert_eq!(
+            DriversQuery::new("Test").query(),
+            [("search_term", "Test".to_string())]
+        );
+        assert_eq!(
+            DriversQuery::new("Test").league_id(4403).query(),
+            [
+                ("search_term", "Test".to_string()),
+                ("league_id", "4403".to_string())
+            ]
+        );
+    }
+}
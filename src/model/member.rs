@@ -0,0 +1,292 @@
+use super::results::Helmet;
+use crate::endpoint::Endpoint;
+use std::collections::HashMap;
+use {
+    chrono::{DateTime, NaiveDate, Utc},
+    serde::{Deserialize, Serialize},
+};
+
+/// Request for the account of the logged in member, from `/data/member/info`
+pub struct MemberInfoQuery;
+
+impl Endpoint for MemberInfoQuery {
+    type Response = MemberInfo;
+    const PATH: &'static str = "/data/member/info";
+}
+
+/// Request for several members at once, from `/data/member/get`
+pub struct MembersQuery {
+    cust_ids: Vec<u32>,
+    include_licenses: bool,
+}
+
+impl MembersQuery {
+    pub fn new(cust_ids: impl IntoIterator<Item = u32>) -> Self {
+        Self {
+            cust_ids: cust_ids.into_iter().collect(),
+            include_licenses: false,
+        }
+    }
+
+    pub fn include_licenses(mut self, include_licenses: bool) -> Self {
+        self.include_licenses = include_licenses;
+        self
+    }
+}
+
+impl Endpoint for MembersQuery {
+    type Response = Members;
+    const PATH: &'static str = "/data/member/get";
+
+    fn query(&self) -> Vec<(&'static str, String)> {
+        let cust_ids: Vec<_> = self.cust_ids.iter().map(u32::to_string).collect();
+        vec![
+            ("cust_ids", cust_ids.join(",")),
+            ("include_licenses", self.include_licenses.to_string()),
+        ]
+    }
+}
+
+/// Request for the public profile of a member, from `/data/member/profile`
+pub struct MemberProfileQuery {
+    cust_id: u32,
+}
+
+impl MemberProfileQuery {
+    pub fn new(cust_id: u32) -> Self {
+        Self { cust_id }
+    }
+}
+
+impl Endpoint for MemberProfileQuery {
+    type Response = MemberProfile;
+    const PATH: &'static str = "/data/member/profile";
+
+    fn query(&self) -> Vec<(&'static str, String)> {
+        vec![("cust_id", self.cust_id.to_string())]
+    }
+}
+
+/// The account of the logged in member
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct MemberInfo {
+    pub cust_id: u32,
+    pub email: Option<String>,
+    pub username: Option<String>,
+    pub display_name: String,
+    pub first_name: String,
+    pub last_name: String,
+    pub on_car_name: Option<String>,
+    pub member_since: NaiveDate,
+    pub last_login: DateTime<Utc>,
+    pub club_id: u32,
+    pub club_name: String,
+    pub connection_type: Option<String>,
+    pub download_server: Option<String>,
+    /// Account flags, as a bitfield
+    pub flags: u32,
+    pub account: Account,
+    pub helmet: Helmet,
+    /// Keyed by category, e.g. `oval` or `road`
+    pub licenses: HashMap<String, License>,
+    #[serde(default)]
+    pub car_packages: Vec<Package>,
+    #[serde(default)]
+    pub track_packages: Vec<Package>,
+    #[serde(default)]
+    pub other_owned_packages: Vec<u32>,
+    #[serde(default)]
+    pub dev: bool,
+    #[serde(default)]
+    pub alpha_tester: bool,
+    #[serde(default)]
+    pub rain_tester: bool,
+    #[serde(default)]
+    pub broadcaster: bool,
+    #[serde(default)]
+    pub hundred_pct_club: bool,
+    #[serde(default)]
+    pub twenty_pct_discount: bool,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct Account {
+    pub ir_dollars: f64,
+    pub ir_credits: f64,
+    pub status: String,
+}
+
+/// Content owned by a member
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct Package {
+    pub package_id: u32,
+    pub content_ids: Vec<u32>,
+}
+
+/// A member's license in one category
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct License {
+    pub category_id: u32,
+    pub category: String,
+    pub category_name: Option<String>,
+    pub license_level: u32,
+    pub safety_rating: f32,
+    pub cpi: f32,
+    pub irating: Option<i32>,
+    pub tt_rating: Option<i32>,
+    #[serde(default)]
+    pub mpr_num_races: u32,
+    #[serde(default)]
+    pub mpr_num_tts: u32,
+    /// The color of the license class, as a hex RGB value
+    pub color: String,
+    /// The license class, e.g. `Class A`
+    pub group_name: String,
+    pub group_id: u32,
+    #[serde(default)]
+    pub pro_promotable: bool,
+    #[serde(default)]
+    pub seq: u32,
+}
+
+/// The response of `/data/member/get`
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct Members {
+    pub success: bool,
+    pub cust_ids: Vec<u32>,
+    pub members: Vec<Member>,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct Member {
+    pub cust_id: u32,
+    pub display_name: String,
+    pub helmet: Helmet,
+    pub last_login: Option<DateTime<Utc>>,
+    pub member_since: NaiveDate,
+    pub club_id: u32,
+    pub club_name: String,
+    #[serde(default)]
+    pub ai: bool,
+    pub flair_id: Option<u32>,
+    pub flair_name: Option<String>,
+    /// Only included when requested with
+    /// [include_licenses](MembersQuery::include_licenses)
+    #[serde(default)]
+    pub licenses: Vec<License>,
+}
+
+/// The public profile of a member
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct MemberProfile {
+    pub cust_id: u32,
+    #[serde(default)]
+    pub disabled: bool,
+    pub member_info: ProfileInfo,
+    pub activity: Option<Activity>,
+    #[serde(default)]
+    pub recent_events: Vec<RecentEvent>,
+    pub image_url: Option<String>,
+    #[serde(default)]
+    pub is_generic_image: bool,
+    pub follow_counts: Option<FollowCounts>,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct ProfileInfo {
+    pub cust_id: u32,
+    pub display_name: String,
+    pub helmet: Helmet,
+    pub last_login: Option<DateTime<Utc>>,
+    pub member_since: NaiveDate,
+    pub club_id: u32,
+    pub club_name: String,
+    #[serde(default)]
+    pub ai: bool,
+    pub flair_id: Option<u32>,
+    pub flair_name: Option<String>,
+    /// Keyed by category, e.g. `oval` or `road`
+    #[serde(default)]
+    pub licenses: HashMap<String, License>,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct Activity {
+    pub recent_30days_count: u32,
+    pub prev_30days_count: u32,
+    pub consecutive_weeks: u32,
+    pub most_consecutive_weeks: u32,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct RecentEvent {
+    pub event_type: String,
+    pub subsession_id: u32,
+    pub start_time: DateTime<Utc>,
+    pub event_name: String,
+    pub starting_position: i32,
+    pub finish_position: i32,
+    pub best_lap_time: i32,
+    pub car_id: u32,
+    pub car_name: String,
+    pub track: RecentEventTrack,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct RecentEventTrack {
+    pub track_id: u32,
+    pub track_name: String,
+    pub config_name: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct FollowCounts {
+    pub followers: u32,
+    pub follows: u32,
+}
+
+#[cfg(test)]
+mod test {
+    use super::{MemberInfo, MemberProfile, Members, MembersQuery};
+    use crate::endpoint::Endpoint;
+
+    #[test]
+    fn member_info_deserialize() {
+        let info: MemberInfo =
+            serde_json::from_str(include_str!("../../test_files/member/info.json")).unwrap();
+        assert_eq!(info.cust_id, 123456);
+        assert_eq!(info.licenses["road"].group_name, "Class B");
+        assert_eq!(info.licenses["road"].irating, Some(2150));
+        assert_eq!(info.car_packages[0].content_ids, [67]);
+    }
+
+    #[test]
+    fn members_deserialize() {
+        let members: Members =
+            serde_json::from_str(include_str!("../../test_files/member/get.json")).unwrap();
+        assert_eq!(members.members.len(), 2);
+        assert_eq!(members.members[0].licenses.len(), 2);
+        assert!(members.members[1].licenses.is_empty());
+    }
+
+    #[test]
+    fn member_profile_deserialize() {
+        let profile: MemberProfile =
+            serde_json::from_str(include_str!("../../test_files/member/profile.json")).unwrap();
+        assert_eq!(profile.member_info.display_name, "Test Driver");
+        assert_eq!(profile.recent_events[0].track.track_name, "Lime Rock Park");
+        assert_eq!(profile.activity.unwrap().consecutive_weeks, 3);
+    }
+
+    #[test]
+    fn members_query_params() {
+        let query = MembersQuery::new([1, 2, 3]).include_licenses(true);
+        assert_eq!(
+            query.query(),
+            [
+                ("cust_ids", "1,2,3".to_string()),
+                ("include_licenses", "true".to_string())
+            ]
+        );
+    }
+}
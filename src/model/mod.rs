@@ -1,5 +1,16 @@
 pub mod auth;
 pub mod car;
 pub mod car_class;
+pub mod chunk_info;
+pub mod event_log;
+pub mod lap_chart;
+pub mod lap_data;
+pub mod lookup;
+pub mod member;
 pub mod results;
+pub mod search;
+pub mod season;
 pub mod season_results;
+pub mod series;
+pub mod stats;
+pub mod track;
@@ -1,8 +1,47 @@
+use crate::{cache::CacheTtl, endpoint::Endpoint};
 use {
     chrono::{DateTime, Utc},
     serde::{Deserialize, Serialize},
 };
 
+/// Request for the results of a subsession, from `/data/results/get`
+pub struct SessionResultsQuery {
+    subsession_id: u32,
+    include_licenses: bool,
+}
+
+impl SessionResultsQuery {
+    pub fn new(subsession_id: u32) -> Self {
+        Self {
+            subsession_id,
+            include_licenses: false,
+        }
+    }
+
+    pub fn include_licenses(mut self, include_licenses: bool) -> Self {
+        self.include_licenses = include_licenses;
+        self
+    }
+}
+
+impl Endpoint for SessionResultsQuery {
+    type Response = SessionResult;
+    const PATH: &'static str = "/data/results/get";
+
+    fn query(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("subsession_id", self.subsession_id.to_string()),
+            ("include_licenses", self.include_licenses.to_string()),
+        ]
+    }
+
+    /// Results are only available once a subsession has finished, and never
+    /// change afterwards
+    fn cache_ttl(&self) -> Option<CacheTtl> {
+        Some(CacheTtl::Forever)
+    }
+}
+
 #[derive(Deserialize, Serialize, Clone, Debug)]
 pub struct SessionResult {
     pub subsession_id: u32,
@@ -0,0 +1,561 @@
+use super::{
+    chunk_info::ChunkInfo,
+    results::Helmet,
+    season_results::{EventType, Track},
+};
+use crate::endpoint::{ChunkedEndpoint, Endpoint, QueryError};
+use {
+    chrono::{DateTime, Duration, SecondsFormat, Utc},
+    serde::{Deserialize, Serialize},
+};
+
+/// The longest time range iRacing accepts in a search
+const MAX_RANGE_DAYS: i64 = 90;
+
+/// A time range to search in. `end` may only be `None` if `begin` is less than
+/// 90 days ago, the range then ends now.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct TimeRange {
+    begin: DateTime<Utc>,
+    end: Option<DateTime<Utc>>,
+}
+
+impl TimeRange {
+    fn validate(&self) -> Result<(), QueryError> {
+        let (begin, end) = match self.end {
+            Some(end) => (self.begin, end),
+            None if Utc::now() - self.begin > Duration::days(MAX_RANGE_DAYS) => {
+                return Err(QueryError::MissingRangeEnd { begin: self.begin });
+            }
+            None => return Ok(()),
+        };
+        if end < begin {
+            return Err(QueryError::InvertedRange { begin, end });
+        }
+        if end - begin > Duration::days(MAX_RANGE_DAYS) {
+            return Err(QueryError::RangeTooLong { begin, end });
+        }
+        Ok(())
+    }
+
+    fn push_params(
+        &self,
+        query: &mut Vec<(&'static str, String)>,
+        names: (&'static str, &'static str),
+    ) {
+        query.push((names.0, format_time(self.begin)));
+        if let Some(end) = self.end {
+            query.push((names.1, format_time(end)));
+        }
+    }
+}
+
+fn format_time(time: DateTime<Utc>) -> String {
+    time.to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+fn join<T: ToString>(values: &[T]) -> String {
+    let values: Vec<_> = values.iter().map(T::to_string).collect();
+    values.join(",")
+}
+
+/// Validate optional start and finish ranges, requiring at least one of them
+/// unless `has_season` is set
+fn validate_ranges(
+    start_range: &Option<TimeRange>,
+    finish_range: &Option<TimeRange>,
+    has_season: bool,
+) -> Result<(), QueryError> {
+    if start_range.is_none() && finish_range.is_none() && !has_season {
+        return Err(QueryError::MissingTimeFrame);
+    }
+    for range in start_range.iter().chain(finish_range) {
+        range.validate()?;
+    }
+    Ok(())
+}
+
+/// Search official series results, from `/data/results/search_series`
+///
+/// Either a season or a start or finish time range is required. Time ranges
+/// may not be longer than 90 days.
+#[derive(Default)]
+pub struct SeriesSearchQuery {
+    season: Option<(u32, u32)>,
+    start_range: Option<TimeRange>,
+    finish_range: Option<TimeRange>,
+    cust_id: Option<u32>,
+    team_id: Option<u32>,
+    series_id: Option<u32>,
+    race_week_num: Option<u32>,
+    official_only: Option<bool>,
+    event_types: Vec<EventType>,
+    category_ids: Vec<u32>,
+}
+
+impl SeriesSearchQuery {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn season(mut self, season_year: u32, season_quarter: u32) -> Self {
+        self.season = Some((season_year, season_quarter));
+        self
+    }
+
+    /// Sessions starting between `begin` and `end`, or now if `end` is `None`
+    pub fn start_range(mut self, begin: DateTime<Utc>, end: Option<DateTime<Utc>>) -> Self {
+        self.start_range = Some(TimeRange { begin, end });
+        self
+    }
+
+    /// Sessions finishing between `begin` and `end`, or now if `end` is `None`
+    pub fn finish_range(mut self, begin: DateTime<Utc>, end: Option<DateTime<Utc>>) -> Self {
+        self.finish_range = Some(TimeRange { begin, end });
+        self
+    }
+
+    /// Sessions a driver took part in. Results are returned for that driver
+    /// only.
+    pub fn cust_id(mut self, cust_id: u32) -> Self {
+        self.cust_id = Some(cust_id);
+        self
+    }
+
+    pub fn team_id(mut self, team_id: u32) -> Self {
+        self.team_id = Some(team_id);
+        self
+    }
+
+    pub fn series_id(mut self, series_id: u32) -> Self {
+        self.series_id = Some(series_id);
+        self
+    }
+
+    pub fn race_week_num(mut self, race_week_num: u32) -> Self {
+        self.race_week_num = Some(race_week_num);
+        self
+    }
+
+    pub fn official_only(mut self, official_only: bool) -> Self {
+        self.official_only = Some(official_only);
+        self
+    }
+
+    pub fn event_types(mut self, event_types: impl IntoIterator<Item = EventType>) -> Self {
+        self.event_types = event_types.into_iter().collect();
+        self
+    }
+
+    pub fn category_ids(mut self, category_ids: impl IntoIterator<Item = u32>) -> Self {
+        self.category_ids = category_ids.into_iter().collect();
+        self
+    }
+}
+
+impl Endpoint for SeriesSearchQuery {
+    type Response = SearchResults;
+    const PATH: &'static str = "/data/results/search_series";
+
+    fn query(&self) -> Vec<(&'static str, String)> {
+        let mut query = Vec::new();
+        if let Some((season_year, season_quarter)) = self.season {
+            query.push(("season_year", season_year.to_string()));
+            query.push(("season_quarter", season_quarter.to_string()));
+        }
+        if let Some(range) = &self.start_range {
+            range.push_params(&mut query, ("start_range_begin", "start_range_end"));
+        }
+        if let Some(range) = &self.finish_range {
+            range.push_params(&mut query, ("finish_range_begin", "finish_range_end"));
+        }
+        if let Some(cust_id) = self.cust_id {
+            query.push(("cust_id", cust_id.to_string()));
+        }
+        if let Some(team_id) = self.team_id {
+            query.push(("team_id", team_id.to_string()));
+        }
+        if let Some(series_id) = self.series_id {
+            query.push(("series_id", series_id.to_string()));
+        }
+        if let Some(race_week_num) = self.race_week_num {
+            query.push(("race_week_num", race_week_num.to_string()));
+        }
+        if let Some(official_only) = self.official_only {
+            query.push(("official_only", official_only.to_string()));
+        }
+        if !self.event_types.is_empty() {
+            query.push(("event_types", join(&self.event_types)));
+        }
+        if !self.category_ids.is_empty() {
+            query.push(("category_ids", join(&self.category_ids)));
+        }
+        query
+    }
+
+    fn validate(&self) -> Result<(), QueryError> {
+        validate_ranges(&self.start_range, &self.finish_range, self.season.is_some())
+    }
+}
+
+impl ChunkedEndpoint for SeriesSearchQuery {
+    type Row = SeriesSearchResult;
+
+    fn chunk_info(response: &SearchResults) -> Option<&ChunkInfo> {
+        response.data.chunk_info.as_ref()
+    }
+}
+
+/// Search hosted and league sessions, from `/data/results/search_hosted`
+///
+/// A start or finish time range is required, and may not be longer than 90
+/// days.
+#[derive(Default)]
+pub struct HostedSearchQuery {
+    start_range: Option<TimeRange>,
+    finish_range: Option<TimeRange>,
+    host_cust_id: Option<u32>,
+    cust_id: Option<u32>,
+    session_name: Option<String>,
+    league_id: Option<u32>,
+    car_id: Option<u32>,
+    track_id: Option<u32>,
+    category_ids: Vec<u32>,
+}
+
+impl HostedSearchQuery {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sessions starting between `begin` and `end`, or now if `end` is `None`
+    pub fn start_range(mut self, begin: DateTime<Utc>, end: Option<DateTime<Utc>>) -> Self {
+        self.start_range = Some(TimeRange { begin, end });
+        self
+    }
+
+    /// Sessions finishing between `begin` and `end`, or now if `end` is `None`
+    pub fn finish_range(mut self, begin: DateTime<Utc>, end: Option<DateTime<Utc>>) -> Self {
+        self.finish_range = Some(TimeRange { begin, end });
+        self
+    }
+
+    /// Sessions hosted by a member
+    pub fn host_cust_id(mut self, host_cust_id: u32) -> Self {
+        self.host_cust_id = Some(host_cust_id);
+        self
+    }
+
+    /// Sessions a driver took part in
+    pub fn cust_id(mut self, cust_id: u32) -> Self {
+        self.cust_id = Some(cust_id);
+        self
+    }
+
+    /// Sessions whose name contains `session_name`
+    pub fn session_name(mut self, session_name: &str) -> Self {
+        self.session_name = Some(session_name.to_string());
+        self
+    }
+
+    pub fn league_id(mut self, league_id: u32) -> Self {
+        self.league_id = Some(league_id);
+        self
+    }
+
+    pub fn car_id(mut self, car_id: u32) -> Self {
+        self.car_id = Some(car_id);
+        self
+    }
+
+    pub fn track_id(mut self, track_id: u32) -> Self {
+        self.track_id = Some(track_id);
+        self
+    }
+
+    pub fn category_ids(mut self, category_ids: impl IntoIterator<Item = u32>) -> Self {
+        self.category_ids = category_ids.into_iter().collect();
+        self
+    }
+}
+
+impl Endpoint for HostedSearchQuery {
+    type Response = SearchResults;
+    const PATH: &'static str = "/data/results/search_hosted";
+
+    fn query(&self) -> Vec<(&'static str, String)> {
+        let mut query = Vec::new();
+        if let Some(range) = &self.start_range {
+            range.push_params(&mut query, ("start_range_begin", "start_range_end"));
+        }
+        if let Some(range) = &self.finish_range {
+            range.push_params(&mut query, ("finish_range_begin", "finish_range_end"));
+        }
+        if let Some(host_cust_id) = self.host_cust_id {
+            query.push(("host_cust_id", host_cust_id.to_string()));
+        }
+        if let Some(cust_id) = self.cust_id {
+            query.push(("cust_id", cust_id.to_string()));
+        }
+        if let Some(session_name) = &self.session_name {
+            query.push(("session_name", session_name.clone()));
+        }
+        if let Some(league_id) = self.league_id {
+            query.push(("league_id", league_id.to_string()));
+        }
+        if let Some(car_id) = self.car_id {
+            query.push(("car_id", car_id.to_string()));
+        }
+        if let Some(track_id) = self.track_id {
+            query.push(("track_id", track_id.to_string()));
+        }
+        if !self.category_ids.is_empty() {
+            query.push(("category_ids", join(&self.category_ids)));
+        }
+        query
+    }
+
+    fn validate(&self) -> Result<(), QueryError> {
+        validate_ranges(&self.start_range, &self.finish_range, false)
+    }
+}
+
+impl ChunkedEndpoint for HostedSearchQuery {
+    type Row = HostedSearchResult;
+
+    fn chunk_info(response: &SearchResults) -> Option<&ChunkInfo> {
+        response.data.chunk_info.as_ref()
+    }
+}
+
+/// The response of the search endpoints. The results themselves are split
+/// into chunks.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct SearchResults {
+    pub data: SearchData,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct SearchData {
+    pub success: bool,
+    /// `None` if nothing matched
+    pub chunk_info: Option<ChunkInfo>,
+}
+
+/// The result of one driver in an official session, in a chunk of
+/// `/data/results/search_series`
+///
+/// Times are in ten-thousandths of a second.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct SeriesSearchResult {
+    pub session_id: u32,
+    pub subsession_id: u32,
+    pub start_time: DateTime<Utc>,
+    pub end_time: DateTime<Utc>,
+    pub season_id: u32,
+    pub season_year: u32,
+    pub season_quarter: u32,
+    pub series_id: u32,
+    pub series_name: String,
+    pub series_short_name: String,
+    pub race_week_num: u32,
+    pub event_type: EventType,
+    pub event_type_name: String,
+    pub official_session: bool,
+    pub license_category_id: u32,
+    pub license_category: String,
+    pub track: Track,
+    pub num_drivers: u32,
+    pub num_cautions: Option<i32>,
+    pub num_caution_laps: Option<i32>,
+    pub num_lead_changes: Option<i32>,
+    pub driver_changes: bool,
+    pub event_strength_of_field: Option<i32>,
+    pub event_best_lap_time: Option<i32>,
+    pub winner_group_id: i32,
+    pub winner_name: String,
+    #[serde(default)]
+    pub winner_ai: bool,
+    pub cust_id: Option<u32>,
+    pub team_id: Option<u32>,
+    pub display_name: Option<String>,
+    pub car_id: u32,
+    pub car_name: Option<String>,
+    pub car_class_id: u32,
+    pub car_class_name: Option<String>,
+    pub starting_position: Option<i32>,
+    pub finish_position: Option<i32>,
+    pub starting_position_in_class: Option<i32>,
+    pub finish_position_in_class: Option<i32>,
+    pub laps_complete: Option<i32>,
+    pub laps_led: Option<i32>,
+    pub incidents: Option<i32>,
+    pub champ_points: Option<i32>,
+}
+
+/// A hosted or league session, in a chunk of `/data/results/search_hosted`
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct HostedSearchResult {
+    pub session_id: u32,
+    pub subsession_id: u32,
+    pub session_name: String,
+    pub start_time: DateTime<Utc>,
+    pub end_time: DateTime<Utc>,
+    pub created: Option<DateTime<Utc>>,
+    pub license_category_id: u32,
+    pub license_category: String,
+    pub track: Track,
+    pub num_drivers: u32,
+    pub num_cautions: Option<i32>,
+    pub num_caution_laps: Option<i32>,
+    pub num_lead_changes: Option<i32>,
+    pub driver_changes: bool,
+    pub winner_group_id: i32,
+    pub winner_name: String,
+    #[serde(default)]
+    pub winner_ai: bool,
+    pub private_session_id: Option<i32>,
+    pub league_id: Option<u32>,
+    pub league_season_id: Option<u32>,
+    pub host: Option<SessionHost>,
+    #[serde(default)]
+    pub cars: Vec<HostedCar>,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct SessionHost {
+    pub cust_id: u32,
+    pub display_name: String,
+    pub helmet: Option<Helmet>,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct HostedCar {
+    pub car_id: u32,
+    pub car_name: String,
+    pub car_class_id: Option<u32>,
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        HostedSearchQuery, HostedSearchResult, SearchResults, SeriesSearchQuery, SeriesSearchResult,
+    };
+    use crate::{
+        endpoint::{Endpoint, QueryError},
+        model::season_results::EventType,
+    };
+    use chrono::{DateTime, Duration, Utc};
+
+    fn time(time: &str) -> DateTime<Utc> {
+        time.parse().unwrap()
+    }
+
+    #[test]
+    fn series_search_query_params() {
+        let query = SeriesSearchQuery::new()
+            .start_range(
+                time("2022-01-01T00:00:00Z"),
+                Some(time("2022-02-01T00:00:00Z")),
+            )
+            .series_id(139)
+            .official_only(true)
+            .event_types([EventType::Qualify, EventType::Race])
+            .category_ids([2]);
+        assert_eq!(query.validate(), Ok(()));
+        assert_eq!(
+            query.query(),
+            [
+                ("start_range_begin", "2022-01-01T00:00:00Z".to_string()),
+                ("start_range_end", "2022-02-01T00:00:00Z".to_string()),
+                ("series_id", "139".to_string()),
+                ("official_only", "true".to_string()),
+                ("event_types", "3,5".to_string()),
+                ("category_ids", "2".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn series_search_requires_time_frame() {
+        assert_eq!(
+            SeriesSearchQuery::new().cust_id(123456).validate(),
+            Err(QueryError::MissingTimeFrame)
+        );
+        assert_eq!(SeriesSearchQuery::new().season(2022, 1).validate(), Ok(()));
+        assert_eq!(
+            SeriesSearchQuery::new()
+                .finish_range(Utc::now() - Duration::days(30), None)
+                .validate(),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn search_open_range_limited_to_90_days_ago() {
+        let begin = Utc::now() - Duration::days(89);
+        let query = HostedSearchQuery::new().start_range(begin, None);
+        assert_eq!(query.validate(), Ok(()));
+
+        let begin = time("2020-01-01T00:00:00Z");
+        let query = SeriesSearchQuery::new().start_range(begin, None);
+        assert_eq!(query.validate(), Err(QueryError::MissingRangeEnd { begin }));
+        let query = SeriesSearchQuery::new().start_range(begin, Some(begin + Duration::days(90)));
+        assert_eq!(query.validate(), Ok(()));
+    }
+
+    #[test]
+    fn search_range_limited_to_90_days() {
+        let begin = time("2022-01-01T00:00:00Z");
+        let query = HostedSearchQuery::new().start_range(begin, Some(begin + Duration::days(90)));
+        assert_eq!(query.validate(), Ok(()));
+
+        let end = begin + Duration::days(91);
+        let query = HostedSearchQuery::new().finish_range(begin, Some(end));
+        assert_eq!(
+            query.validate(),
+            Err(QueryError::RangeTooLong { begin, end })
+        );
+
+        let query = SeriesSearchQuery::new()
+            .season(2022, 1)
+            .start_range(end, Some(begin));
+        assert_eq!(
+            query.validate(),
+            Err(QueryError::InvertedRange {
+                begin: end,
+                end: begin
+            })
+        );
+    }
+
+    #[test]
+    fn hosted_search_requires_time_range() {
+        assert_eq!(
+            HostedSearchQuery::new().host_cust_id(123456).validate(),
+            Err(QueryError::MissingTimeFrame)
+        );
+    }
+
+    #[test]
+    fn search_results_deserialize() {
+        let results: SearchResults =
+            serde_json::from_str(include_str!("../../test_files/search/search_series.json"))
+                .unwrap();
+        assert_eq!(results.data.chunk_info.unwrap().rows, 2);
+
+        let rows: Vec<SeriesSearchResult> = serde_json::from_str(include_str!(
+            "../../test_files/search/search_series_chunk.json"
+        ))
+        .unwrap();
+        assert_eq!(rows[0].event_type, EventType::Race);
+        assert_eq!(rows[1].finish_position, Some(1));
+
+        let rows: Vec<HostedSearchResult> = serde_json::from_str(include_str!(
+            "../../test_files/search/search_hosted_chunk.json"
+        ))
+        .unwrap();
+        assert_eq!(rows[0].host.as_ref().unwrap().cust_id, 123456);
+        assert_eq!(rows[0].cars[0].car_id, 67);
+    }
+}
@@ -0,0 +1,136 @@
+use crate::endpoint::Endpoint;
+use {
+    chrono::{DateTime, SecondsFormat, Utc},
+    serde::{Deserialize, Serialize},
+};
+
+/// Request for the sessions starting soon, from `/data/season/race_guide`
+pub struct RaceGuideQuery {
+    from: Option<DateTime<Utc>>,
+    include_end_after_from: bool,
+}
+
+impl RaceGuideQuery {
+    pub fn new() -> Self {
+        Self {
+            from: None,
+            include_end_after_from: false,
+        }
+    }
+
+    /// Only include sessions starting after `from`. Defaults to now.
+    pub fn from(mut self, from: DateTime<Utc>) -> Self {
+        self.from = Some(from);
+        self
+    }
+
+    /// Also include sessions that started before `from`, but end after it
+    pub fn include_end_after_from(mut self, include_end_after_from: bool) -> Self {
+        self.include_end_after_from = include_end_after_from;
+        self
+    }
+}
+
+impl Default for RaceGuideQuery {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Endpoint for RaceGuideQuery {
+    type Response = RaceGuide;
+    const PATH: &'static str = "/data/season/race_guide";
+
+    fn query(&self) -> Vec<(&'static str, String)> {
+        let mut query = Vec::new();
+        if let Some(from) = self.from {
+            query.push(("from", from.to_rfc3339_opts(SecondsFormat::Secs, true)));
+        }
+        query.push((
+            "include_end_after_from",
+            self.include_end_after_from.to_string(),
+        ));
+        query
+    }
+}
+
+/// The sessions starting within a few hours of the requested time
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct RaceGuide {
+    pub subscribed: bool,
+    pub sessions: Vec<RaceGuideSession>,
+    pub block_begin_time: DateTime<Utc>,
+    pub block_end_time: DateTime<Utc>,
+    pub success: bool,
+}
+
+impl RaceGuide {
+    /// The sessions of a series, in the order they start
+    pub fn upcoming(&self, series_id: u32) -> impl Iterator<Item = &RaceGuideSession> {
+        let mut sessions: Vec<_> = self
+            .sessions
+            .iter()
+            .filter(|session| session.series_id == series_id)
+            .collect();
+        sessions.sort_by_key(|session| session.start_time);
+        sessions.into_iter()
+    }
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct RaceGuideSession {
+    pub season_id: u32,
+    pub series_id: u32,
+    pub race_week_num: u32,
+    pub start_time: DateTime<Utc>,
+    pub end_time: DateTime<Utc>,
+    /// Only set once registration for the session has opened
+    pub session_id: Option<u32>,
+    pub entry_count: u32,
+    pub super_session: bool,
+}
+
+#[cfg(test)]
+mod test {
+    use super::{RaceGuide, RaceGuideQuery};
+    use crate::endpoint::Endpoint;
+    use chrono::{DateTime, Utc};
+
+    #[test]
+    fn race_guide_deserialize() {
+        let guide: RaceGuide =
+            serde_json::from_str(include_str!("../../test_files/season/race_guide.json")).unwrap();
+        assert_eq!(guide.sessions.len(), 4);
+
+        let upcoming: Vec<_> = guide
+            .upcoming(139)
+            .map(|session| session.start_time)
+            .collect();
+        assert_eq!(
+            upcoming,
+            [
+                "2022-01-20T18:45:00Z".parse::<DateTime<Utc>>().unwrap(),
+                "2022-01-20T20:45:00Z".parse::<DateTime<Utc>>().unwrap()
+            ]
+        );
+        assert_eq!(
+            guide.upcoming(139).next().unwrap().session_id,
+            Some(172345678)
+        );
+    }
+
+    #[test]
+    fn race_guide_query_params() {
+        let from = "2022-01-20T18:00:00Z".parse::<DateTime<Utc>>().unwrap();
+        assert_eq!(
+            RaceGuideQuery::new()
+                .from(from)
+                .include_end_after_from(true)
+                .query(),
+            [
+                ("from", "2022-01-20T18:00:00Z".to_string()),
+                ("include_end_after_from", "true".to_string())
+            ]
+        );
+    }
+}
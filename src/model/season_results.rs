@@ -1,3 +1,4 @@
+use crate::endpoint::Endpoint;
 use std::fmt;
 use {
     serde::{Deserialize, Serialize},
@@ -41,6 +42,15 @@ impl SeasonResultsQuery {
     }
 }
 
+impl Endpoint for SeasonResultsQuery {
+    type Response = SeasonResults;
+    const PATH: &'static str = "/data/results/season_results";
+
+    fn query(&self) -> Vec<(&'static str, String)> {
+        self.as_query_params()
+    }
+}
+
 #[derive(Deserialize_repr, Serialize_repr, Clone, Copy, Debug, PartialEq, Eq)]
 #[repr(u8)]
 pub enum EventType {
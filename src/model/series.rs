@@ -0,0 +1,467 @@
+use super::{results::SessionAlowedLicense, season_results::SeasonResultsQuery};
+use crate::{
+    cache::CacheTtl,
+    client::ApiError,
+    endpoint::{decode_asset_map, Endpoint},
+};
+use std::collections::{BTreeSet, HashMap};
+use {
+    chrono::{DateTime, Duration, NaiveDate, NaiveTime, TimeZone, Utc},
+    serde::{Deserialize, Serialize},
+};
+
+/// Request for every series, from `/data/series/get`
+pub struct SeriesQuery;
+
+impl Endpoint for SeriesQuery {
+    type Response = Vec<Series>;
+    const PATH: &'static str = "/data/series/get";
+
+    fn cache_ttl(&self) -> Option<CacheTtl> {
+        Some(CacheTtl::DAY)
+    }
+}
+
+/// Request for the current seasons of every series, with their schedules, from
+/// `/data/series/seasons`
+pub struct SeriesSeasonsQuery {
+    include_series: bool,
+}
+
+impl SeriesSeasonsQuery {
+    pub fn new() -> Self {
+        Self {
+            include_series: false,
+        }
+    }
+
+    pub fn include_series(mut self, include_series: bool) -> Self {
+        self.include_series = include_series;
+        self
+    }
+}
+
+impl Default for SeriesSeasonsQuery {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Endpoint for SeriesSeasonsQuery {
+    type Response = Vec<Season>;
+    const PATH: &'static str = "/data/series/seasons";
+
+    fn query(&self) -> Vec<(&'static str, String)> {
+        vec![("include_series", self.include_series.to_string())]
+    }
+}
+
+/// Request for every season of a series, from `/data/series/past_seasons`
+pub struct PastSeasonsQuery {
+    series_id: u32,
+}
+
+impl PastSeasonsQuery {
+    pub fn new(series_id: u32) -> Self {
+        Self { series_id }
+    }
+}
+
+impl Endpoint for PastSeasonsQuery {
+    type Response = PastSeasons;
+    const PATH: &'static str = "/data/series/past_seasons";
+
+    fn query(&self) -> Vec<(&'static str, String)> {
+        vec![("series_id", self.series_id.to_string())]
+    }
+
+    fn cache_ttl(&self) -> Option<CacheTtl> {
+        Some(CacheTtl::DAY)
+    }
+}
+
+/// Request for the assets of every series, from `/data/series/assets`
+///
+/// The response maps series IDs to asset data, like the actual API.
+pub struct SeriesAssetsQuery;
+
+impl Endpoint for SeriesAssetsQuery {
+    type Response = HashMap<u32, SeriesAssets>;
+    const PATH: &'static str = "/data/series/assets";
+
+    fn cache_ttl(&self) -> Option<CacheTtl> {
+        Some(CacheTtl::DAY)
+    }
+
+    fn decode(&self, body: &[u8]) -> Result<Self::Response, ApiError> {
+        decode_asset_map(Self::PATH, body)
+    }
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct Series {
+    pub series_id: u32,
+    pub series_name: String,
+    pub series_short_name: String,
+    pub category: String,
+    pub category_id: u32,
+    pub allowed_licenses: Vec<SessionAlowedLicense>,
+    pub eligible: bool,
+    pub first_season: Option<SeasonNumber>,
+    pub forum_url: Option<String>,
+    pub max_starters: u32,
+    pub min_starters: u32,
+}
+
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SeasonNumber {
+    pub season_year: u32,
+    pub season_quarter: u32,
+}
+
+/// A current season of a series, with its schedule
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct Season {
+    pub season_id: u32,
+    pub series_id: u32,
+    pub season_name: String,
+    pub season_short_name: String,
+    pub season_year: u32,
+    pub season_quarter: u32,
+    pub active: bool,
+    pub complete: bool,
+    pub official: bool,
+    pub fixed_setup: bool,
+    pub multiclass: bool,
+    pub driver_changes: bool,
+    pub license_group: u32,
+    pub car_class_ids: Vec<u32>,
+    /// The current race week, starting at 0
+    pub race_week: u32,
+    pub max_weeks: u32,
+    pub start_date: NaiveDate,
+    pub schedule_description: Option<String>,
+    pub schedules: Vec<Schedule>,
+}
+
+impl Season {
+    /// The schedule of a race week, starting at 0
+    pub fn schedule(&self, race_week_num: u32) -> Option<&Schedule> {
+        self.schedules
+            .iter()
+            .find(|schedule| schedule.race_week_num == race_week_num)
+    }
+
+    /// The schedule of the current race week
+    pub fn current_schedule(&self) -> Option<&Schedule> {
+        self.schedule(self.race_week)
+    }
+
+    /// The start times of the sessions from the current race week until the
+    /// end of the season, in order
+    pub fn start_times(&self) -> Vec<DateTime<Utc>> {
+        let start_times: BTreeSet<_> = self
+            .schedules
+            .iter()
+            .filter(|schedule| schedule.race_week_num >= self.race_week)
+            .flat_map(Schedule::start_times)
+            .collect();
+        start_times.into_iter().collect()
+    }
+}
+
+/// The schedule of one race week of a season
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct Schedule {
+    pub season_id: u32,
+    /// Starting at 0, like [SeasonResults::race_week_num](super::season_results::SeasonResults::race_week_num)
+    pub race_week_num: u32,
+    pub series_id: u32,
+    pub series_name: String,
+    pub season_name: String,
+    pub schedule_name: String,
+    pub start_date: NaiveDate,
+    pub race_lap_limit: Option<u32>,
+    /// In minutes
+    pub race_time_limit: Option<u32>,
+    pub start_type: String,
+    pub restart_type: String,
+    pub qual_attached: bool,
+    pub track: ScheduleTrack,
+    pub race_time_descriptors: Vec<RaceTimeDescriptor>,
+    #[serde(default)]
+    pub car_restrictions: Vec<CarRestriction>,
+}
+
+impl Schedule {
+    /// A query for the results of this race week
+    pub fn results_query(&self) -> SeasonResultsQuery {
+        SeasonResultsQuery::new(self.season_id).race_week_num(self.race_week_num)
+    }
+
+    /// The start times of the sessions of this race week, in order
+    ///
+    /// Only sessions starting within the week from `start_date` are included,
+    /// later ones belong to the next race week.
+    pub fn start_times(&self) -> Vec<DateTime<Utc>> {
+        let week_start = Utc.from_utc_datetime(&self.start_date.and_hms_opt(0, 0, 0).unwrap());
+        let week_end = week_start + Duration::days(7);
+        let start_times: BTreeSet<_> = self
+            .race_time_descriptors
+            .iter()
+            .flat_map(RaceTimeDescriptor::start_times)
+            .filter(|start_time| (week_start..week_end).contains(start_time))
+            .collect();
+        start_times.into_iter().collect()
+    }
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct ScheduleTrack {
+    pub track_id: u32,
+    pub track_name: String,
+    pub config_name: Option<String>,
+    pub category: Option<String>,
+    pub category_id: Option<u32>,
+}
+
+/// When the sessions of a race week start
+///
+/// Repeating sessions start every `repeat_minutes` from `first_session_time`,
+/// for `session_minutes`, on the days in `day_offset` counted from
+/// `start_date`. Other sessions start at each of the `session_times`.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct RaceTimeDescriptor {
+    pub repeating: bool,
+    pub super_session: bool,
+    pub session_minutes: Option<u32>,
+    pub start_date: Option<NaiveDate>,
+    pub first_session_time: Option<NaiveTime>,
+    pub repeat_minutes: Option<u32>,
+    #[serde(default)]
+    pub day_offset: Vec<u32>,
+    #[serde(default)]
+    pub session_times: Vec<DateTime<Utc>>,
+}
+
+impl RaceTimeDescriptor {
+    /// The start times of the sessions described, in order. Without a
+    /// `session_minutes`, repeating sessions start until the end of the day.
+    pub fn start_times(&self) -> Vec<DateTime<Utc>> {
+        if !self.repeating {
+            let mut start_times = self.session_times.clone();
+            start_times.sort();
+            return start_times;
+        }
+        let (start_date, first_session_time, repeat_minutes) = match (
+            self.start_date,
+            self.first_session_time,
+            self.repeat_minutes,
+        ) {
+            (Some(date), Some(time), Some(repeat_minutes)) if repeat_minutes > 0 => {
+                (date, time, repeat_minutes)
+            }
+            _ => return Vec::new(),
+        };
+        let first_start = Utc.from_utc_datetime(&start_date.and_time(first_session_time));
+        let window = Duration::minutes(self.session_minutes.unwrap_or(24 * 60).into());
+
+        let mut start_times = BTreeSet::new();
+        for &day_offset in &self.day_offset {
+            let first_start = first_start + Duration::days(day_offset.into());
+            let mut start_time = first_start;
+            while start_time - first_start < window {
+                start_times.insert(start_time);
+                start_time += Duration::minutes(repeat_minutes.into());
+            }
+        }
+        start_times.into_iter().collect()
+    }
+}
+
+/// Balance of performance applied to a car during a race week
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct CarRestriction {
+    pub car_id: u32,
+    pub max_pct_fuel_fill: i32,
+    pub weight_penalty_kg: i32,
+    pub power_adjust_pct: f32,
+    #[serde(default)]
+    pub max_dry_tire_sets: u32,
+    pub race_setup_id: Option<u32>,
+    pub qual_setup_id: Option<u32>,
+}
+
+/// The response of `/data/series/past_seasons`
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct PastSeasons {
+    pub series_id: u32,
+    pub series: PastSeries,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct PastSeries {
+    pub series_id: u32,
+    pub series_name: String,
+    pub series_short_name: String,
+    pub category: String,
+    pub category_id: u32,
+    pub active: bool,
+    pub official: bool,
+    pub seasons: Vec<PastSeason>,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct PastSeason {
+    pub season_id: u32,
+    pub series_id: u32,
+    pub season_name: String,
+    pub season_short_name: String,
+    pub season_year: u32,
+    pub season_quarter: u32,
+    pub active: bool,
+    pub official: bool,
+    pub driver_changes: bool,
+    pub fixed_setup: bool,
+    pub license_group: u32,
+    #[serde(default)]
+    pub car_classes: Vec<PastSeasonCarClass>,
+    #[serde(default)]
+    pub race_weeks: Vec<PastRaceWeek>,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct PastSeasonCarClass {
+    pub car_class_id: u32,
+    pub name: String,
+    pub short_name: String,
+    pub relative_speed: u32,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct PastRaceWeek {
+    pub season_id: u32,
+    pub race_week_num: u32,
+    pub track: ScheduleTrack,
+}
+
+impl PastRaceWeek {
+    /// A query for the results of this race week
+    pub fn results_query(&self) -> SeasonResultsQuery {
+        SeasonResultsQuery::new(self.season_id).race_week_num(self.race_week_num)
+    }
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct SeriesAssets {
+    pub series_id: u32,
+    pub logo: Option<String>,
+    pub large_image: Option<String>,
+    pub small_image: Option<String>,
+    pub series_copy: Option<String>,
+}
+
+#[cfg(test)]
+mod test {
+    use super::{PastSeasons, RaceTimeDescriptor, Season, Series, SeriesAssetsQuery};
+    use crate::endpoint::Endpoint;
+    use chrono::{DateTime, Utc};
+
+    fn utc(time: &str) -> DateTime<Utc> {
+        time.parse().unwrap()
+    }
+
+    #[test]
+    fn race_time_descriptor_start_times() {
+        let mut descriptor: RaceTimeDescriptor = serde_json::from_str(
+            r#"{
+                "repeating": true,
+                "super_session": false,
+                "session_minutes": 300,
+                "start_date": "2021-12-14",
+                "first_session_time": "01:30:00",
+                "repeat_minutes": 120,
+                "day_offset": [0, 2]
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            descriptor.start_times(),
+            [
+                utc("2021-12-14T01:30:00Z"),
+                utc("2021-12-14T03:30:00Z"),
+                utc("2021-12-14T05:30:00Z"),
+                utc("2021-12-16T01:30:00Z"),
+                utc("2021-12-16T03:30:00Z"),
+                utc("2021-12-16T05:30:00Z"),
+            ]
+        );
+
+        descriptor.repeating = false;
+        descriptor.session_times = vec![utc("2021-12-18T18:00:00Z"), utc("2021-12-15T18:00:00Z")];
+        assert_eq!(
+            descriptor.start_times(),
+            [utc("2021-12-15T18:00:00Z"), utc("2021-12-18T18:00:00Z")]
+        );
+    }
+
+    #[test]
+    fn season_start_times() {
+        let seasons: Vec<Season> =
+            serde_json::from_str(include_str!("../../test_files/series/seasons.json")).unwrap();
+        let start_times = seasons[0].start_times();
+
+        // Every two hours during the current race week only, which starts on
+        // 2021-12-21
+        assert_eq!(start_times.len(), 7 * 12);
+        assert_eq!(start_times[0], utc("2021-12-21T00:45:00Z"));
+        assert_eq!(start_times[1], utc("2021-12-21T02:45:00Z"));
+        assert_eq!(start_times[83], utc("2021-12-27T22:45:00Z"));
+    }
+
+    #[test]
+    fn series_deserialize() {
+        let series: Vec<Series> =
+            serde_json::from_str(include_str!("../../test_files/series/get.json")).unwrap();
+        assert_eq!(series[0].series_id, 139);
+        assert_eq!(series[0].allowed_licenses.len(), 2);
+    }
+
+    #[test]
+    fn season_deserialize() {
+        let seasons: Vec<Season> =
+            serde_json::from_str(include_str!("../../test_files/series/seasons.json")).unwrap();
+        let season = &seasons[0];
+        let schedule = season.current_schedule().unwrap();
+        assert_eq!(schedule.race_week_num, 1);
+        assert_eq!(schedule.track.track_name, "Lime Rock Park");
+        assert_eq!(schedule.car_restrictions[0].weight_penalty_kg, 5);
+        assert_eq!(schedule.race_time_descriptors[0].repeat_minutes, Some(120));
+
+        let query = schedule.results_query().as_query_params();
+        assert_eq!(
+            query,
+            [
+                ("season_id", "3416".to_string()),
+                ("race_week_num", "1".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn past_seasons_deserialize() {
+        let past: PastSeasons =
+            serde_json::from_str(include_str!("../../test_files/series/past_seasons.json"))
+                .unwrap();
+        assert_eq!(past.series.seasons.len(), 2);
+        assert_eq!(past.series.seasons[1].race_weeks[0].track.track_id, 14);
+    }
+
+    #[test]
+    fn series_assets_deserialize() {
+        let assets = SeriesAssetsQuery
+            .decode(include_bytes!("../../test_files/series/assets.json"))
+            .unwrap();
+        assert_eq!(assets[&139].logo.as_deref(), Some("mx5cup-logo.png"));
+    }
+}
@@ -0,0 +1,259 @@
+use crate::endpoint::Endpoint;
+use serde::{Deserialize, Serialize};
+
+/// Request for the career statistics of a member per category, from
+/// `/data/stats/member_career`
+pub struct MemberCareerQuery {
+    cust_id: u32,
+}
+
+impl MemberCareerQuery {
+    pub fn new(cust_id: u32) -> Self {
+        Self { cust_id }
+    }
+}
+
+impl Endpoint for MemberCareerQuery {
+    type Response = MemberCareer;
+    const PATH: &'static str = "/data/stats/member_career";
+
+    fn query(&self) -> Vec<(&'static str, String)> {
+        vec![("cust_id", self.cust_id.to_string())]
+    }
+}
+
+/// Request for the statistics of a member per year and category, from
+/// `/data/stats/member_yearly`
+pub struct MemberYearlyQuery {
+    cust_id: u32,
+}
+
+impl MemberYearlyQuery {
+    pub fn new(cust_id: u32) -> Self {
+        Self { cust_id }
+    }
+}
+
+impl Endpoint for MemberYearlyQuery {
+    type Response = MemberYearly;
+    const PATH: &'static str = "/data/stats/member_yearly";
+
+    fn query(&self) -> Vec<(&'static str, String)> {
+        vec![("cust_id", self.cust_id.to_string())]
+    }
+}
+
+/// Request for the number of sessions and wins of a member this year, from
+/// `/data/stats/member_summary`
+pub struct MemberSummaryQuery {
+    cust_id: u32,
+}
+
+impl MemberSummaryQuery {
+    pub fn new(cust_id: u32) -> Self {
+        Self { cust_id }
+    }
+}
+
+impl Endpoint for MemberSummaryQuery {
+    type Response = MemberSummary;
+    const PATH: &'static str = "/data/stats/member_summary";
+
+    fn query(&self) -> Vec<(&'static str, String)> {
+        vec![("cust_id", self.cust_id.to_string())]
+    }
+}
+
+/// Request for a recap of a member's year or season, from
+/// `/data/stats/member_recap`
+pub struct MemberRecapQuery {
+    cust_id: u32,
+    year: Option<u32>,
+    season: Option<u32>,
+}
+
+impl MemberRecapQuery {
+    pub fn new(cust_id: u32) -> Self {
+        Self {
+            cust_id,
+            year: None,
+            season: None,
+        }
+    }
+
+    /// Defaults to the current year
+    pub fn year(mut self, year: u32) -> Self {
+        self.year = Some(year);
+        self
+    }
+
+    /// The season (i.e. quarter) of the year to recap. Defaults to the whole
+    /// year.
+    pub fn season(mut self, season: u32) -> Self {
+        self.season = Some(season);
+        self
+    }
+}
+
+impl Endpoint for MemberRecapQuery {
+    type Response = MemberRecap;
+    const PATH: &'static str = "/data/stats/member_recap";
+
+    fn query(&self) -> Vec<(&'static str, String)> {
+        let mut query = vec![("cust_id", self.cust_id.to_string())];
+        if let Some(year) = self.year {
+            query.push(("year", year.to_string()));
+        }
+        if let Some(season) = self.season {
+            query.push(("season", season.to_string()));
+        }
+        query
+    }
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct MemberCareer {
+    pub cust_id: u32,
+    pub stats: Vec<CategoryStats>,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct MemberYearly {
+    pub cust_id: u32,
+    pub stats: Vec<YearlyStats>,
+}
+
+/// The statistics of a member in one category, e.g. `Road`
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct CategoryStats {
+    pub category_id: u32,
+    pub category: String,
+    pub starts: u32,
+    pub wins: u32,
+    pub top5: u32,
+    pub poles: u32,
+    pub avg_start_position: u32,
+    pub avg_finish_position: u32,
+    pub laps: u32,
+    pub laps_led: u32,
+    pub avg_incidents: f32,
+    pub avg_points: i32,
+    pub win_percentage: f32,
+    pub top5_percentage: f32,
+    pub laps_led_percentage: f32,
+    pub poles_percentage: f32,
+    pub total_club_points: i32,
+}
+
+/// The statistics of a member in one category during one year
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct YearlyStats {
+    pub year: u32,
+    #[serde(flatten)]
+    pub stats: CategoryStats,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct MemberSummary {
+    pub cust_id: u32,
+    pub this_year: SummaryStats,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct SummaryStats {
+    pub num_official_sessions: u32,
+    pub num_league_sessions: u32,
+    pub num_official_wins: u32,
+    pub num_league_wins: u32,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct MemberRecap {
+    pub cust_id: u32,
+    pub year: u32,
+    /// `None` if the recap covers the whole year
+    pub season: Option<u32>,
+    pub stats: RecapStats,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct RecapStats {
+    pub starts: u32,
+    pub wins: u32,
+    pub top5: u32,
+    pub avg_start_position: u32,
+    pub avg_finish_position: u32,
+    pub laps: u32,
+    pub laps_led: u32,
+    /// `None` if the member did not race
+    pub favorite_car: Option<FavoriteCar>,
+    pub favorite_track: Option<FavoriteTrack>,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct FavoriteCar {
+    pub car_id: u32,
+    pub car_name: String,
+    pub car_image: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct FavoriteTrack {
+    pub track_id: u32,
+    pub track_name: String,
+    pub config_name: Option<String>,
+    pub track_logo: Option<String>,
+}
+
+#[cfg(test)]
+mod test {
+    use super::{MemberCareer, MemberRecap, MemberRecapQuery, MemberSummary, MemberYearly};
+    use crate::endpoint::Endpoint;
+
+    #[test]
+    fn member_career_deserialize() {
+        let career: MemberCareer =
+            serde_json::from_str(include_str!("../../test_files/stats/member_career.json"))
+                .unwrap();
+        assert_eq!(career.stats.len(), 2);
+        assert_eq!(career.stats[1].category, "Road");
+        assert_eq!(career.stats[1].wins, 12);
+    }
+
+    #[test]
+    fn member_yearly_deserialize() {
+        let yearly: MemberYearly =
+            serde_json::from_str(include_str!("../../test_files/stats/member_yearly.json"))
+                .unwrap();
+        assert_eq!(yearly.stats[0].year, 2022);
+        assert_eq!(yearly.stats[0].stats.starts, 8);
+    }
+
+    #[test]
+    fn member_summary_deserialize() {
+        let summary: MemberSummary =
+            serde_json::from_str(include_str!("../../test_files/stats/member_summary.json"))
+                .unwrap();
+        assert_eq!(summary.this_year.num_official_sessions, 8);
+    }
+
+    #[test]
+    fn member_recap_deserialize() {
+        let recap: MemberRecap =
+            serde_json::from_str(include_str!("../../test_files/stats/member_recap.json")).unwrap();
+        assert_eq!(recap.season, Some(1));
+        assert_eq!(recap.stats.favorite_car.unwrap().car_id, 67);
+    }
+
+    #[test]
+    fn member_recap_query_params() {
+        assert_eq!(
+            MemberRecapQuery::new(123456).year(2022).season(1).query(),
+            [
+                ("cust_id", "123456".to_string()),
+                ("year", "2022".to_string()),
+                ("season", "1".to_string())
+            ]
+        );
+    }
+}
@@ -0,0 +1,161 @@
+use crate::{
+    cache::CacheTtl,
+    client::ApiError,
+    endpoint::{decode_asset_map, Endpoint},
+};
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::PathBuf,
+};
+use {
+    chrono::{DateTime, Utc},
+    serde::{Deserialize, Serialize},
+};
+
+/// Request for every track configuration, from `/data/track/get`
+pub struct TracksQuery;
+
+impl Endpoint for TracksQuery {
+    type Response = Vec<Track>;
+    const PATH: &'static str = "/data/track/get";
+
+    fn cache_ttl(&self) -> Option<CacheTtl> {
+        Some(CacheTtl::DAY)
+    }
+}
+
+/// Request for the assets of every track, from `/data/track/assets`
+///
+/// The response maps track IDs to asset data, like the actual API.
+pub struct TrackAssetsQuery;
+
+impl Endpoint for TrackAssetsQuery {
+    type Response = HashMap<u32, TrackAssets>;
+    const PATH: &'static str = "/data/track/assets";
+
+    fn cache_ttl(&self) -> Option<CacheTtl> {
+        Some(CacheTtl::DAY)
+    }
+
+    fn decode(&self, body: &[u8]) -> Result<Self::Response, ApiError> {
+        decode_asset_map(Self::PATH, body)
+    }
+}
+
+/// One configuration of a track, as returned by `/data/track/get`
+///
+/// Every configuration of a track is a separate entry, sharing the
+/// `package_id` of the track. See [configs_by_package].
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Track {
+    pub track_id: u32,
+    pub track_name: String,
+    pub config_name: Option<String>,
+    pub category: String,
+    pub category_id: u32,
+    pub track_types: Vec<TrackType>,
+    pub track_dirpath: String,
+    pub package_id: u32,
+    pub sku: u32,
+    /// In miles
+    pub track_config_length: f32,
+    pub corners_per_lap: u32,
+    /// In the unit used where the track is located, e.g. mph in the USA
+    pub pit_road_speed_limit: Option<u32>,
+    pub max_cars: u32,
+    pub grid_stalls: u32,
+    pub number_pitstalls: u32,
+    pub night_lighting: bool,
+    pub fully_lit: bool,
+    pub is_dirt: bool,
+    pub is_oval: bool,
+    pub location: String,
+    pub latitude: f64,
+    pub longitude: f64,
+    pub time_zone: String,
+    pub created: DateTime<Utc>,
+    pub price: f32,
+    pub free_with_subscription: bool,
+    pub purchasable: bool,
+    pub retired: bool,
+    pub ai_enabled: bool,
+    pub award_exempt: bool,
+    pub has_svg_map: bool,
+    pub search_filters: String,
+    pub site_url: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TrackType {
+    pub track_type: String,
+}
+
+/// Group track configurations by the package they belong to, i.e. by
+/// physical track
+pub fn configs_by_package(tracks: &[Track]) -> BTreeMap<u32, Vec<&Track>> {
+    let mut packages: BTreeMap<u32, Vec<&Track>> = BTreeMap::new();
+    for track in tracks {
+        packages.entry(track.package_id).or_default().push(track);
+    }
+    packages
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TrackAssets {
+    pub track_id: u32,
+    pub coordinates: Option<String>,
+    pub detail_copy: Option<String>,
+    pub detail_techspecs_copy: Option<String>,
+    pub folder: PathBuf,
+    pub gallery_images: Option<String>,
+    pub gallery_prefix: Option<String>,
+    pub large_image: Option<PathBuf>,
+    pub logo: Option<PathBuf>,
+    pub small_image: Option<PathBuf>,
+    /// The URL the map layers are relative to
+    pub track_map: Option<String>,
+    pub track_map_layers: Option<TrackMapLayers>,
+}
+
+/// SVG files making up the map of a track, relative to
+/// [TrackAssets::track_map]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TrackMapLayers {
+    pub background: String,
+    pub inactive: String,
+    pub active: String,
+    pub pitroad: String,
+    #[serde(rename = "start-finish")]
+    pub start_finish: String,
+    pub turns: String,
+}
+
+#[cfg(test)]
+mod test {
+    use super::{configs_by_package, Track, TrackAssetsQuery};
+    use crate::endpoint::Endpoint;
+
+    #[test]
+    fn track_deserialize() {
+        let tracks: Vec<Track> =
+            serde_json::from_str(include_str!("../../test_files/track/get.json")).unwrap();
+        assert_eq!(tracks.len(), 3);
+        assert_eq!(tracks[0].track_name, "Lime Rock Park");
+        assert_eq!(tracks[0].pit_road_speed_limit, Some(45));
+
+        let packages = configs_by_package(&tracks);
+        assert_eq!(packages.len(), 2);
+        assert_eq!(packages[&9].len(), 2);
+        assert_eq!(packages[&9][1].config_name.as_deref(), Some("Chicanes"));
+    }
+
+    #[test]
+    fn track_assets_deserialize() {
+        let assets = TrackAssetsQuery
+            .decode(include_bytes!("../../test_files/track/assets.json"))
+            .unwrap();
+        let layers = assets[&14].track_map_layers.as_ref().unwrap();
+        assert_eq!(layers.start_finish, "start-finish.svg");
+        assert!(assets[&18].track_map_layers.is_none());
+    }
+}
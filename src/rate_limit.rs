@@ -0,0 +1,184 @@
+use std::{sync::Mutex, time::Duration};
+use {
+    chrono::{DateTime, TimeZone, Utc},
+    reqwest::header::HeaderMap,
+};
+
+/// The request budget reported by iRacing in the `x-ratelimit-*` headers of
+/// every `/data` response
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimit {
+    /// The number of requests allowed per window
+    pub limit: u32,
+    /// The number of requests left in the current window
+    pub remaining: u32,
+    /// When the current window ends and `remaining` is reset to `limit`
+    pub reset: DateTime<Utc>,
+}
+
+impl RateLimit {
+    /// Read the rate limit from a response's headers, if all three are present
+    /// and well-formed
+    pub fn from_headers(headers: &HeaderMap) -> Option<Self> {
+        let header = |name| headers.get(name)?.to_str().ok()?.trim().parse::<i64>().ok();
+
+        Some(Self {
+            limit: header("x-ratelimit-limit")?.try_into().ok()?,
+            remaining: header("x-ratelimit-remaining")?.try_into().ok()?,
+            reset: Utc
+                .timestamp_opt(header("x-ratelimit-reset")?, 0)
+                .single()?,
+        })
+    }
+
+    /// How long to wait at `now` before a request can be made without being
+    /// throttled
+    pub fn delay(&self, now: DateTime<Utc>) -> Option<Duration> {
+        if self.remaining > 0 {
+            return None;
+        }
+        (self.reset - now)
+            .to_std()
+            .ok()
+            .filter(|delay| !delay.is_zero())
+    }
+}
+
+/// Keeps track of the most recently reported [RateLimit]
+#[derive(Debug)]
+pub(crate) struct RateLimiter {
+    state: Mutex<Option<RateLimit>>,
+    /// Whether to wait for the budget to reset instead of sending requests
+    /// that will be throttled
+    wait: bool,
+}
+
+impl RateLimiter {
+    pub(crate) fn new(wait: bool) -> Self {
+        Self {
+            state: Mutex::default(),
+            wait,
+        }
+    }
+
+    pub(crate) fn current(&self) -> Option<RateLimit> {
+        *self.state.lock().unwrap()
+    }
+
+    /// Reserve one request from the budget, waiting for the budget to reset
+    /// first if it is exhausted and waiting is enabled
+    pub(crate) async fn acquire(&self) {
+        loop {
+            let delay = {
+                // Check and count the request under the same lock, so
+                // concurrent requests don't all take the last remaining request
+                let mut state = self.state.lock().unwrap();
+                let limit = match state.as_mut() {
+                    Some(limit) => limit,
+                    None => return,
+                };
+                let now = Utc::now();
+                if limit.reset <= now {
+                    limit.remaining = limit.limit;
+                }
+                match limit.delay(now).filter(|_| self.wait) {
+                    Some(delay) => delay,
+                    None => {
+                        limit.remaining = limit.remaining.saturating_sub(1);
+                        return;
+                    }
+                }
+            };
+            tokio::time::sleep(delay).await;
+        }
+    }
+
+    /// Record the budget reported by a response, if it reported one
+    pub(crate) fn update(&self, headers: &HeaderMap) {
+        if let Some(limit) = RateLimit::from_headers(headers) {
+            *self.state.lock().unwrap() = Some(limit);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{RateLimit, RateLimiter};
+    use chrono::{Duration, TimeZone, Utc};
+    use reqwest::header::{HeaderMap, HeaderValue};
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    fn headers(limit: &'static str, remaining: &'static str, reset: &'static str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-ratelimit-limit", HeaderValue::from_static(limit));
+        headers.insert("x-ratelimit-remaining", HeaderValue::from_static(remaining));
+        headers.insert("x-ratelimit-reset", HeaderValue::from_static(reset));
+        headers
+    }
+
+    #[test]
+    fn rate_limit_from_headers() {
+        let limit = RateLimit::from_headers(&headers("240", "239", "1641600000")).unwrap();
+        assert_eq!(
+            limit,
+            RateLimit {
+                limit: 240,
+                remaining: 239,
+                reset: Utc.timestamp_opt(1641600000, 0).unwrap(),
+            }
+        );
+    }
+
+    #[test]
+    fn rate_limit_from_headers_missing_or_invalid() {
+        assert_eq!(RateLimit::from_headers(&HeaderMap::new()), None);
+        assert_eq!(
+            RateLimit::from_headers(&headers("240", "-1", "1641600000")),
+            None
+        );
+    }
+
+    #[test]
+    fn rate_limit_delay() {
+        let now = Utc.timestamp_opt(1641600000, 0).unwrap();
+        let mut limit = RateLimit {
+            limit: 240,
+            remaining: 1,
+            reset: now + Duration::seconds(30),
+        };
+        assert_eq!(limit.delay(now), None);
+
+        limit.remaining = 0;
+        assert_eq!(limit.delay(now), Some(std::time::Duration::from_secs(30)));
+        assert_eq!(limit.delay(now + Duration::seconds(31)), None);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn rate_limiter_concurrent_acquire() {
+        let limiter = Arc::new(RateLimiter::new(true));
+        *limiter.state.lock().unwrap() = Some(RateLimit {
+            limit: 240,
+            remaining: 3,
+            reset: Utc::now() + Duration::seconds(60),
+        });
+
+        let acquired = Arc::new(AtomicUsize::new(0));
+        let tasks: Vec<_> = (0..16)
+            .map(|_| {
+                let (limiter, acquired) = (limiter.clone(), acquired.clone());
+                tokio::spawn(async move {
+                    limiter.acquire().await;
+                    acquired.fetch_add(1, Ordering::SeqCst);
+                })
+            })
+            .collect();
+        tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+
+        assert_eq!(acquired.load(Ordering::SeqCst), 3);
+        assert_eq!(limiter.current().unwrap().remaining, 0);
+        tasks.iter().for_each(|task| task.abort());
+    }
+}
@@ -0,0 +1,180 @@
+use crate::transport::TransportError;
+use std::time::Duration;
+use {rand::Rng, reqwest::StatusCode};
+
+/// Controls how failed requests are retried
+///
+/// The client applies a policy separately to the `/data` request and to the
+/// fetch of the link it returns, so a failed link fetch only retries the
+/// fetch.
+///
+/// The delay before retry `n` is `initial_backoff * 2^(n - 1)`, capped at
+/// `max_backoff`. With jitter enabled, a random delay between half and all of
+/// that is used instead, so that many clients don't retry in lockstep.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    jitter: bool,
+    retry_statuses: Vec<StatusCode>,
+    retry_connection_errors: bool,
+}
+
+impl RetryPolicy {
+    /// A policy that never retries
+    pub fn none() -> Self {
+        Self::default().max_attempts(1)
+    }
+
+    /// The total number of attempts, including the first one. Values below 1
+    /// are treated as 1.
+    pub fn max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
+    pub fn initial_backoff(mut self, initial_backoff: Duration) -> Self {
+        self.initial_backoff = initial_backoff;
+        self
+    }
+
+    pub fn max_backoff(mut self, max_backoff: Duration) -> Self {
+        self.max_backoff = max_backoff;
+        self
+    }
+
+    pub fn jitter(mut self, jitter: bool) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// The HTTP statuses that should be retried
+    pub fn retry_statuses(mut self, statuses: impl IntoIterator<Item = StatusCode>) -> Self {
+        self.retry_statuses = statuses.into_iter().collect();
+        self
+    }
+
+    /// Whether to retry when a request could not be sent or its response
+    /// could not be read, e.g. on timeouts
+    pub fn retry_connection_errors(mut self, retry: bool) -> Self {
+        self.retry_connection_errors = retry;
+        self
+    }
+
+    /// Whether another attempt may be made after `attempt` (starting at 1)
+    /// failed
+    pub(crate) fn can_retry(&self, attempt: u32) -> bool {
+        attempt < self.max_attempts
+    }
+
+    pub(crate) fn retries_status(&self, status: StatusCode) -> bool {
+        self.retry_statuses.contains(&status)
+    }
+
+    pub(crate) fn retries_error(&self, err: &TransportError) -> bool {
+        self.retry_connection_errors && err.kind.is_transient()
+    }
+
+    /// The delay to wait after `attempt` (starting at 1) failed
+    pub(crate) fn backoff(&self, attempt: u32) -> Duration {
+        let jitter = if self.jitter {
+            rand::thread_rng().gen_range(0.0..=1.0)
+        } else {
+            1.0
+        };
+        self.backoff_with_jitter(attempt, jitter)
+    }
+
+    /// Wait for the backoff after a failed `attempt` and count the next one,
+    /// or return `None` if no more attempts are allowed
+    pub(crate) async fn wait_to_retry(&self, attempt: &mut u32) -> Option<()> {
+        if !self.can_retry(*attempt) {
+            return None;
+        }
+
+        tokio::time::sleep(self.backoff(*attempt)).await;
+        *attempt += 1;
+        Some(())
+    }
+
+    /// `jitter` is between 0 and 1, where 1 means the full exponential delay
+    fn backoff_with_jitter(&self, attempt: u32, jitter: f64) -> Duration {
+        let exponent = attempt.saturating_sub(1).min(31);
+        let delay = self
+            .initial_backoff
+            .saturating_mul(1 << exponent)
+            .min(self.max_backoff);
+        delay / 2 + (delay / 2).mul_f64(jitter)
+    }
+}
+
+impl Default for RetryPolicy {
+    /// Up to 3 attempts, starting with a 500ms backoff, retrying connection
+    /// errors, rate limiting and server errors
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(30),
+            jitter: true,
+            retry_statuses: vec![
+                StatusCode::TOO_MANY_REQUESTS,
+                StatusCode::INTERNAL_SERVER_ERROR,
+                StatusCode::BAD_GATEWAY,
+                StatusCode::SERVICE_UNAVAILABLE,
+                StatusCode::GATEWAY_TIMEOUT,
+            ],
+            retry_connection_errors: true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::RetryPolicy;
+    use reqwest::StatusCode;
+    use std::time::Duration;
+
+    #[test]
+    fn retry_policy_attempts() {
+        let policy = RetryPolicy::default().max_attempts(3);
+        assert!(policy.can_retry(1));
+        assert!(policy.can_retry(2));
+        assert!(!policy.can_retry(3));
+
+        assert!(!RetryPolicy::none().can_retry(1));
+        assert!(!RetryPolicy::default().max_attempts(0).can_retry(1));
+    }
+
+    #[test]
+    fn retry_policy_statuses() {
+        let policy = RetryPolicy::default().retry_statuses([StatusCode::BAD_GATEWAY]);
+        assert!(policy.retries_status(StatusCode::BAD_GATEWAY));
+        assert!(!policy.retries_status(StatusCode::TOO_MANY_REQUESTS));
+    }
+
+    #[test]
+    fn retry_policy_exponential_backoff() {
+        let policy = RetryPolicy::default()
+            .initial_backoff(Duration::from_secs(1))
+            .max_backoff(Duration::from_secs(5))
+            .jitter(false);
+        assert_eq!(policy.backoff(1), Duration::from_secs(1));
+        assert_eq!(policy.backoff(2), Duration::from_secs(2));
+        assert_eq!(policy.backoff(3), Duration::from_secs(4));
+        assert_eq!(policy.backoff(4), Duration::from_secs(5));
+        assert_eq!(policy.backoff(100), Duration::from_secs(5));
+    }
+
+    #[test]
+    fn retry_policy_jitter_bounds() {
+        let policy = RetryPolicy::default().initial_backoff(Duration::from_secs(2));
+        assert_eq!(policy.backoff_with_jitter(1, 0.0), Duration::from_secs(1));
+        assert_eq!(policy.backoff_with_jitter(1, 1.0), Duration::from_secs(2));
+        for _ in 0..100 {
+            let backoff = policy.backoff(1);
+            assert!(backoff >= Duration::from_secs(1) && backoff <= Duration::from_secs(2));
+        }
+    }
+}
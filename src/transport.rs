@@ -0,0 +1,246 @@
+//! The HTTP layer used by [IracingApiClient](crate::IracingApiClient)
+//!
+//! Every request made by the client goes through a [Transport]. By default
+//! this is a [ReqwestTransport], but any implementation can be provided with
+//! [IracingApiClientBuilder::transport](crate::IracingApiClientBuilder::transport),
+//! e.g. a [MemoryTransport] serving canned responses in tests.
+//!
+//! Real traffic can be recorded to a fixture directory with a
+//! [RecordingTransport], and replayed in tests with a [ReplayTransport].
+
+use std::{error::Error as StdError, fmt, sync::Arc};
+use {
+    async_trait::async_trait,
+    reqwest::{header::HeaderMap, redirect::Policy, Client, Method, StatusCode, Url},
+    thiserror::Error,
+};
+
+mod fixture;
+mod memory;
+
+pub use {
+    fixture::{RecordingTransport, ReplayTransport},
+    memory::MemoryTransport,
+};
+
+/// Sends HTTP requests on behalf of the client
+///
+/// Implementations should not follow redirects to other hosts or manage
+/// cookies, the client attaches the session cookies itself.
+#[async_trait]
+pub trait Transport: Send + Sync {
+    async fn send(&self, request: Request) -> Result<Response, TransportError>;
+}
+
+#[async_trait]
+impl<T: Transport + ?Sized> Transport for Arc<T> {
+    async fn send(&self, request: Request) -> Result<Response, TransportError> {
+        (**self).send(request).await
+    }
+}
+
+#[async_trait]
+impl<T: Transport + ?Sized> Transport for Box<T> {
+    async fn send(&self, request: Request) -> Result<Response, TransportError> {
+        (**self).send(request).await
+    }
+}
+
+/// An HTTP request made by the client
+#[derive(Debug, Clone, PartialEq)]
+pub struct Request {
+    pub method: Method,
+    /// The full URL, including the query
+    pub url: Url,
+    pub headers: HeaderMap,
+    pub body: Option<Vec<u8>>,
+}
+
+impl Request {
+    pub fn new(method: Method, url: Url) -> Self {
+        Self {
+            method,
+            url,
+            headers: HeaderMap::new(),
+            body: None,
+        }
+    }
+}
+
+/// A complete HTTP response, with its body already read
+#[derive(Debug, Clone, PartialEq)]
+pub struct Response {
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+    pub body: Vec<u8>,
+}
+
+impl Response {
+    pub fn new(status: StatusCode, body: impl Into<Vec<u8>>) -> Self {
+        Self {
+            status,
+            headers: HeaderMap::new(),
+            body: body.into(),
+        }
+    }
+
+    /// A `200 OK` response with a JSON body
+    pub fn json(body: &serde_json::Value) -> Self {
+        Self::new(StatusCode::OK, body.to_string())
+    }
+}
+
+/// A request could not be sent, or its response could not be read
+#[derive(Error, Debug, Clone)]
+#[error("{kind}")]
+pub struct TransportError {
+    pub kind: TransportErrorKind,
+    #[source]
+    pub source: Option<Arc<dyn StdError + Send + Sync>>,
+}
+
+impl TransportError {
+    pub fn new(
+        kind: TransportErrorKind,
+        source: impl Into<Box<dyn StdError + Send + Sync>>,
+    ) -> Self {
+        Self {
+            kind,
+            source: Some(Arc::from(source.into())),
+        }
+    }
+}
+
+/// What caused a [TransportError]; used to decide whether a request is worth
+/// retrying
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransportErrorKind {
+    /// The request or reading the response timed out
+    Timeout,
+    /// A connection could not be established
+    Connect,
+    /// The connection failed while sending the request or reading the
+    /// response
+    Io,
+    /// Any other failure, e.g. an invalid request
+    Other,
+}
+
+impl TransportErrorKind {
+    /// Whether the same request might succeed if sent again
+    pub fn is_transient(self) -> bool {
+        !matches!(self, TransportErrorKind::Other)
+    }
+}
+
+impl fmt::Display for TransportErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            TransportErrorKind::Timeout => "Request timed out",
+            TransportErrorKind::Connect => "Cannot connect",
+            TransportErrorKind::Io => "Connection failure",
+            TransportErrorKind::Other => "Request failed",
+        })
+    }
+}
+
+/// The default [Transport], sending requests with a [reqwest::Client]
+#[derive(Debug, Clone)]
+pub struct ReqwestTransport {
+    client: Client,
+}
+
+impl ReqwestTransport {
+    /// Send requests through `client`. The client does not need a cookie
+    /// store, and should not follow redirects.
+    pub fn new(client: Client) -> Self {
+        Self { client }
+    }
+}
+
+impl Default for ReqwestTransport {
+    /// A transport using a client that does not follow redirects
+    ///
+    /// # Panics
+    ///
+    /// Like [Client::new], panics if a TLS backend cannot be initialized, or
+    /// the resolver cannot load the system configuration.
+    fn default() -> Self {
+        let client = Client::builder()
+            .redirect(Policy::none())
+            .build()
+            .expect("Cannot initialize HTTP client");
+        Self::new(client)
+    }
+}
+
+impl From<reqwest::Error> for TransportError {
+    fn from(err: reqwest::Error) -> Self {
+        let kind = if err.is_timeout() {
+            TransportErrorKind::Timeout
+        } else if err.is_connect() {
+            TransportErrorKind::Connect
+        } else if err.is_request() || err.is_body() || err.is_decode() {
+            TransportErrorKind::Io
+        } else {
+            TransportErrorKind::Other
+        };
+        Self::new(kind, err)
+    }
+}
+
+#[async_trait]
+impl Transport for ReqwestTransport {
+    async fn send(&self, request: Request) -> Result<Response, TransportError> {
+        let mut builder = self
+            .client
+            .request(request.method, request.url)
+            .headers(request.headers);
+        if let Some(body) = request.body {
+            builder = builder.body(body);
+        }
+
+        let response = builder.send().await?;
+        Ok(Response {
+            status: response.status(),
+            headers: response.headers().clone(),
+            body: response.bytes().await?.to_vec(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Request, ReqwestTransport, Transport};
+    use reqwest::{Method, StatusCode, Url};
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpListener,
+    };
+
+    #[tokio::test]
+    async fn reqwest_transport_does_not_follow_redirects() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = Url::parse(&format!(
+            "http://{}/data/car/get",
+            listener.local_addr().unwrap()
+        ))
+        .unwrap();
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut request = [0; 1024];
+            let _ = socket.read(&mut request).await.unwrap();
+            socket
+                .write_all(b"HTTP/1.1 302 Found\r\nLocation: http://example.invalid/login\r\nContent-Length: 0\r\n\r\n")
+                .await
+                .unwrap();
+        });
+
+        let response = ReqwestTransport::default()
+            .send(Request::new(Method::GET, url))
+            .await
+            .unwrap();
+        assert_eq!(response.status, StatusCode::FOUND);
+        assert_eq!(response.headers["location"], "http://example.invalid/login");
+    }
+}
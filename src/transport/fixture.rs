@@ -0,0 +1,424 @@
+use super::{Request, Response, Transport, TransportError, TransportErrorKind};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::Write,
+    fs, io,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+use {
+    async_trait::async_trait,
+    reqwest::{
+        header::{HeaderMap, HeaderName, HeaderValue},
+        Method, StatusCode, Url,
+    },
+    serde::{Deserialize, Serialize},
+    sha2::{Digest, Sha256},
+};
+
+/// The host that recorded links are rewritten to point to
+const FIXTURE_HOST: &str = "https://fixtures.invalid";
+
+/// Keys whose string values are replaced when recording, compared
+/// case-insensitively
+const SCRUBBED_KEYS: &[&str] = &[
+    "authcode",
+    "autologintoken",
+    "email",
+    "password",
+    "ssocookievalue",
+];
+
+const REDACTED_EMAIL: &str = "redacted@example.com";
+
+/// A response as stored in a fixture file
+#[derive(Serialize, Deserialize, Debug)]
+struct Fixture {
+    status: u16,
+    headers: BTreeMap<String, String>,
+    body: FixtureBody,
+}
+
+/// Bodies are stored as JSON when possible, so fixtures are readable and can
+/// be edited by hand
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(untagged)]
+enum FixtureBody {
+    Json(serde_json::Value),
+    Text(String),
+}
+
+/// A [Transport] that sends requests through another transport, and writes
+/// every response to a fixture directory that a [ReplayTransport] can serve
+///
+/// Before a response is written, cookies are dropped, and emails, auth codes
+/// and tokens are replaced. Links to data are rewritten to point to the
+/// fixture of the linked data, so that replays don't depend on them. Requests
+/// are never written, so credentials don't end up in fixtures. Failing to
+/// write a fixture does not fail the request.
+pub struct RecordingTransport<T> {
+    inner: T,
+    dir: PathBuf,
+    /// Maps real links to the fixture URLs they were rewritten to
+    links: Mutex<HashMap<Url, Url>>,
+    /// Maps real chunk base URLs to the fixture base URLs they were rewritten
+    /// to
+    chunk_bases: Mutex<Vec<(String, String)>>,
+}
+
+impl<T: Transport> RecordingTransport<T> {
+    pub fn new(inner: T, dir: impl Into<PathBuf>) -> Self {
+        Self {
+            inner,
+            dir: dir.into(),
+            links: Mutex::default(),
+            chunk_bases: Mutex::default(),
+        }
+    }
+
+    fn record(&self, request: &Request, response: &Response) -> io::Result<()> {
+        let recorded_url = self.recorded_url(&request.url);
+
+        let mut headers = BTreeMap::new();
+        for (name, value) in &response.headers {
+            if is_recorded_header(name) {
+                if let Ok(value) = value.to_str() {
+                    headers.insert(name.to_string(), value.to_string());
+                }
+            }
+        }
+
+        let body = match serde_json::from_slice(&response.body) {
+            Ok(mut json) => {
+                scrub(&mut json);
+                self.rewrite_link(&recorded_url, &mut json);
+                self.rewrite_chunk_info(&recorded_url, &mut json);
+                FixtureBody::Json(json)
+            }
+            Err(_) => FixtureBody::Text(String::from_utf8_lossy(&response.body).into_owned()),
+        };
+
+        let fixture = Fixture {
+            status: response.status.as_u16(),
+            headers,
+            body,
+        };
+        let path = fixture_path(&self.dir, &request.method, &recorded_url);
+        fs::create_dir_all(path.parent().unwrap_or(&self.dir))?;
+        fs::write(&path, serde_json::to_vec_pretty(&fixture)?)
+    }
+
+    /// The URL a request to `url` is recorded as: its fixture URL if it is a
+    /// rewritten link or chunk, or `url` itself
+    fn recorded_url(&self, url: &Url) -> Url {
+        if let Some(fixture_link) = self.links.lock().unwrap().get(url) {
+            return fixture_link.clone();
+        }
+        let chunk_bases = self.chunk_bases.lock().unwrap();
+        chunk_bases
+            .iter()
+            .find_map(|(real_base, fixture_base)| {
+                let file_name = url.as_str().strip_prefix(real_base.as_str())?;
+                Url::parse(&format!("{}{}", fixture_base, file_name)).ok()
+            })
+            .unwrap_or_else(|| url.clone())
+    }
+
+    /// Point the `base_download_url` of any `chunk_info` in a response to a
+    /// fixture URL, remembering the real one so that the chunks are recorded
+    /// under it
+    fn rewrite_chunk_info(&self, recorded_url: &Url, json: &mut serde_json::Value) {
+        match json {
+            serde_json::Value::Object(map) => {
+                if let Some(serde_json::Value::String(base)) = map
+                    .get_mut("chunk_info")
+                    .and_then(|chunk_info| chunk_info.get_mut("base_download_url"))
+                {
+                    let fixture_base = fixture_chunk_base(recorded_url);
+                    let real_base = std::mem::replace(base, fixture_base.clone());
+                    self.chunk_bases
+                        .lock()
+                        .unwrap()
+                        .push((real_base, fixture_base));
+                }
+                map.values_mut()
+                    .for_each(|value| self.rewrite_chunk_info(recorded_url, value));
+            }
+            serde_json::Value::Array(values) => values
+                .iter_mut()
+                .for_each(|value| self.rewrite_chunk_info(recorded_url, value)),
+            _ => {}
+        }
+    }
+
+    /// Point the `link` of a `/data` response to a fixture URL, remembering
+    /// the real link so that the linked data is recorded at that URL
+    fn rewrite_link(&self, recorded_url: &Url, json: &mut serde_json::Value) {
+        let link = match json.get_mut("link") {
+            Some(serde_json::Value::String(link)) => link,
+            _ => return,
+        };
+        if let Ok(real_link) = Url::parse(link) {
+            let fixture_link = fixture_link(recorded_url);
+            *link = fixture_link.to_string();
+            self.links.lock().unwrap().insert(real_link, fixture_link);
+        }
+    }
+}
+
+#[async_trait]
+impl<T: Transport> Transport for RecordingTransport<T> {
+    async fn send(&self, request: Request) -> Result<Response, TransportError> {
+        let response = self.inner.send(request.clone()).await?;
+        // The request itself succeeded, failing to write its fixture (e.g.
+        // because the disk is full) only means it is missing from replays
+        let _ = self.record(&request, &response);
+        Ok(response)
+    }
+}
+
+/// A [Transport] serving the responses written by a [RecordingTransport],
+/// so tests can run deterministically without network access
+///
+/// Requests without a fixture fail with a [TransportError].
+#[derive(Debug, Clone)]
+pub struct ReplayTransport {
+    dir: PathBuf,
+}
+
+impl ReplayTransport {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+}
+
+#[async_trait]
+impl Transport for ReplayTransport {
+    async fn send(&self, request: Request) -> Result<Response, TransportError> {
+        let path = fixture_path(&self.dir, &request.method, &request.url);
+        let contents = fs::read(&path).map_err(|err| {
+            TransportError::new(
+                TransportErrorKind::Other,
+                format!(
+                    "No fixture for {} {} at {:?}: {}",
+                    request.method, request.url, path, err
+                ),
+            )
+        })?;
+        let fixture: Fixture = serde_json::from_slice(&contents)
+            .map_err(|err| TransportError::new(TransportErrorKind::Other, err))?;
+
+        let mut headers = HeaderMap::new();
+        for (name, value) in fixture.headers {
+            if let (Ok(name), Ok(value)) = (
+                HeaderName::from_bytes(name.as_bytes()),
+                HeaderValue::from_str(&value),
+            ) {
+                headers.insert(name, value);
+            }
+        }
+        let body = match fixture.body {
+            FixtureBody::Json(json) => json.to_string().into_bytes(),
+            FixtureBody::Text(text) => text.into_bytes(),
+        };
+
+        Ok(Response {
+            status: StatusCode::from_u16(fixture.status)
+                .map_err(|err| TransportError::new(TransportErrorKind::Other, err))?,
+            headers,
+            body,
+        })
+    }
+}
+
+fn is_recorded_header(name: &HeaderName) -> bool {
+    name == "content-type" || name.as_str().starts_with("x-ratelimit-")
+}
+
+/// The file a response to `method` `url` is recorded in, in a directory
+/// following the path of `url` and named after [request_hash], e.g.
+/// `<dir>/data/results/get/<hash>.json`. The host is ignored.
+fn fixture_path(dir: &Path, method: &Method, url: &Url) -> PathBuf {
+    let mut path = dir.to_path_buf();
+    for segment in url.path_segments().into_iter().flatten() {
+        if !segment.is_empty() {
+            path.push(sanitize(segment));
+        }
+    }
+    path.push(format!("{}.json", request_hash(method, url)));
+    path
+}
+
+/// A hex encoded hash of `method`, the path of `url` and its sorted query
+/// parameters
+fn request_hash(method: &Method, url: &Url) -> String {
+    let mut query: Vec<_> = url.query_pairs().collect();
+    query.sort();
+
+    let mut hasher = Sha256::new();
+    hasher.update(method.as_str());
+    hasher.update([0]);
+    hasher.update(url.path());
+    for (name, value) in query {
+        hasher.update([0]);
+        hasher.update(name.as_bytes());
+        hasher.update([0]);
+        hasher.update(value.as_bytes());
+    }
+    let mut hash = String::with_capacity(64);
+    for byte in hasher.finalize() {
+        let _ = write!(hash, "{:02x}", byte);
+    }
+    hash
+}
+
+/// The fixture URL that a link returned by `url` is rewritten to
+fn fixture_link(url: &Url) -> Url {
+    let mut link = Url::parse(FIXTURE_HOST).unwrap();
+    link.set_path(&format!("/links{}", url.path()));
+    link.set_query(url.query());
+    link
+}
+
+/// The fixture URL that the base URL of chunks returned by `url` is rewritten
+/// to
+fn fixture_chunk_base(url: &Url) -> String {
+    format!(
+        "{}/chunks{}/{}/",
+        FIXTURE_HOST,
+        url.path(),
+        request_hash(&Method::GET, url)
+    )
+}
+
+fn sanitize(part: &str) -> String {
+    part.chars()
+        .map(|c| match c {
+            'a'..='z' | 'A'..='Z' | '0'..='9' | '-' | '_' | '.' | ',' => c,
+            _ => '_',
+        })
+        .collect()
+}
+
+/// Replace emails, auth codes and tokens in a recorded body
+fn scrub(json: &mut serde_json::Value) {
+    match json {
+        serde_json::Value::Object(map) => {
+            for (key, value) in map.iter_mut() {
+                let scrubbed_key = SCRUBBED_KEYS
+                    .iter()
+                    .any(|scrubbed| key.eq_ignore_ascii_case(scrubbed));
+                match value {
+                    serde_json::Value::String(string) if scrubbed_key => {
+                        *string = if looks_like_email(string) {
+                            REDACTED_EMAIL.to_string()
+                        } else {
+                            "redacted".to_string()
+                        }
+                    }
+                    _ => scrub(value),
+                }
+            }
+        }
+        serde_json::Value::Array(values) => values.iter_mut().for_each(scrub),
+        serde_json::Value::String(string) if string.contains('@') => {
+            *string = string
+                .split(' ')
+                .map(|word| {
+                    if looks_like_email(word) {
+                        REDACTED_EMAIL
+                    } else {
+                        word
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join(" ");
+        }
+        _ => {}
+    }
+}
+
+fn looks_like_email(word: &str) -> bool {
+    match word.split_once('@') {
+        Some((user, domain)) => {
+            !user.is_empty() && domain.contains('.') && !word.contains(char::is_whitespace)
+        }
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{fixture_path, scrub};
+    use reqwest::{Method, Url};
+    use std::path::Path;
+
+    #[test]
+    fn fixture_path_is_unique_per_request() {
+        let path = |method: Method, url: &str| {
+            fixture_path(Path::new("fixtures"), &method, &Url::parse(url).unwrap())
+        };
+
+        assert_eq!(
+            path(
+                Method::GET,
+                "https://members-ng.iracing.com/data/results/get?a=1&b=2"
+            ),
+            path(Method::GET, "http://localhost/data/results/get?b=2&a=1")
+        );
+        assert_ne!(
+            path(Method::GET, "https://members-ng.iracing.com/auth"),
+            path(Method::POST, "https://members-ng.iracing.com/auth")
+        );
+        assert_ne!(
+            path(
+                Method::GET,
+                "https://members-ng.iracing.com/data/lookup/drivers?search_term=a/b"
+            ),
+            path(
+                Method::GET,
+                "https://members-ng.iracing.com/data/lookup/drivers?search_term=a_b"
+            )
+        );
+        assert_ne!(
+            path(
+                Method::GET,
+                "https://members-ng.iracing.com/data/results/get?a=1%262"
+            ),
+            path(
+                Method::GET,
+                "https://members-ng.iracing.com/data/results/get?a=1&2"
+            )
+        );
+
+        let long_term = "x".repeat(1000);
+        let long = path(
+            Method::GET,
+            &format!(
+                "https://members-ng.iracing.com/data/lookup/drivers?search_term={}",
+                long_term
+            ),
+        );
+        assert_eq!(
+            long.parent().unwrap(),
+            Path::new("fixtures/data/lookup/drivers")
+        );
+        assert_eq!(long.file_name().unwrap().len(), 69);
+    }
+
+    #[test]
+    fn scrub_credentials_and_emails() {
+        let mut json: serde_json::Value =
+            serde_json::from_str(include_str!("../../test_files/auth/success.json")).unwrap();
+        json["notes"] = serde_json::json!(["Contact someone@example.com", "someone@example.com"]);
+        scrub(&mut json);
+
+        assert_eq!(json["authcode"], "redacted");
+        assert_eq!(json["email"], "redacted@example.com");
+        assert_eq!(json["ssoCookieValue"], "redacted");
+        assert_eq!(json["ssoCookieName"], "irsso_membersv2");
+        assert_eq!(json["custId"], 123456);
+        assert_eq!(json["notes"][0], "Contact redacted@example.com");
+        assert_eq!(json["notes"][1], "redacted@example.com");
+    }
+}
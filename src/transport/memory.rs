@@ -0,0 +1,215 @@
+use super::{Request, Response, Transport, TransportError};
+use std::{
+    collections::VecDeque,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+};
+use {
+    async_trait::async_trait,
+    reqwest::{Method, StatusCode},
+};
+
+/// A [Transport] serving canned responses from memory, so that code using the
+/// client can be tested without network access or iRacing credentials
+///
+/// Requests are matched against routes by method, path and query, ignoring
+/// the host. Requests that match no route get a `404 Not Found`.
+///
+/// ```
+/// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+/// use std::sync::Arc;
+/// use iracing_api::{transport::MemoryTransport, IracingApiClient};
+/// use serde_json::json;
+///
+/// let transport = Arc::new(MemoryTransport::new());
+/// transport.login(&json!({
+///     "authcode": "authcode",
+///     "autoLoginSeries": null,
+///     "autoLoginToken": null,
+///     "custId": 123456,
+///     "email": "email@example.com",
+///     "ssoCookieDomain": ".iracing.com",
+///     "ssoCookieName": "irsso_membersv2",
+///     "ssoCookiePath": "/",
+///     "ssoCookieValue": "sso cookie value"
+/// }));
+/// transport.link("/data/carclass/get", &json!([]));
+///
+/// let client = IracingApiClient::builder()
+///     .credentials("email@example.com", "password")
+///     .transport(transport.clone())
+///     .build()
+///     .await?;
+/// assert!(client.all_car_classes().await?.is_empty());
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Default)]
+pub struct MemoryTransport {
+    routes: Mutex<Vec<Route>>,
+    requests: Mutex<Vec<Request>>,
+    links: AtomicUsize,
+}
+
+#[derive(Debug)]
+struct Route {
+    method: Method,
+    path: String,
+    query: Vec<(String, String)>,
+    /// Served in order, the last one is repeated for any further requests
+    responses: VecDeque<Response>,
+}
+
+impl Route {
+    fn matches(&self, request: &Request) -> bool {
+        self.method == request.method
+            && self.path == request.url.path()
+            && self.query.iter().all(|(name, value)| {
+                request
+                    .url
+                    .query_pairs()
+                    .any(|(n, v)| n == name.as_str() && v == value.as_str())
+            })
+    }
+}
+
+impl MemoryTransport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Respond to requests matching `target` with `response`
+    ///
+    /// `target` is a path, optionally followed by a query, e.g.
+    /// `/data/results/get?subsession_id=1`. A request matches if it has the
+    /// same path and at least the query parameters of the target. When called
+    /// several times for the same route, the responses are served in order,
+    /// and the last one is repeated.
+    pub fn respond(&self, method: Method, target: &str, response: Response) {
+        let (path, query) = target.split_once('?').unwrap_or((target, ""));
+        let query: Vec<_> = url::form_urlencoded::parse(query.as_bytes())
+            .into_owned()
+            .collect();
+
+        let mut routes = self.routes.lock().unwrap();
+        match routes
+            .iter_mut()
+            .find(|route| route.method == method && route.path == path && route.query == query)
+        {
+            Some(route) => route.responses.push_back(response),
+            None => routes.push(Route {
+                method,
+                path: path.to_string(),
+                query,
+                responses: VecDeque::from([response]),
+            }),
+        }
+    }
+
+    /// Respond to logins with `body`, e.g. the contents of a successful
+    /// authentication response
+    pub fn login(&self, body: &serde_json::Value) {
+        self.respond(Method::POST, "/auth", Response::json(body));
+    }
+
+    /// Serve `payload` through the link-wrapped `/data` endpoint `target`
+    ///
+    /// Requests to `target` get a link to a URL serving `payload`, the way
+    /// iRacing links to S3. The links are numbered in the order they were
+    /// added: `/links/0`, `/links/1`, etc.
+    pub fn link(&self, target: &str, payload: &serde_json::Value) {
+        let link_path = format!("/links/{}", self.links.fetch_add(1, Ordering::SeqCst));
+        self.respond(
+            Method::GET,
+            target,
+            Response::json(&serde_json::json!({
+                "link": format!("https://memory.invalid{}", link_path),
+            })),
+        );
+        self.respond(Method::GET, &link_path, Response::json(payload));
+    }
+
+    /// Every request sent so far, in order
+    pub fn requests(&self) -> Vec<Request> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+#[async_trait]
+impl Transport for MemoryTransport {
+    async fn send(&self, request: Request) -> Result<Response, TransportError> {
+        self.requests.lock().unwrap().push(request.clone());
+
+        let mut routes = self.routes.lock().unwrap();
+        let response = match routes.iter_mut().find(|route| route.matches(&request)) {
+            Some(route) if route.responses.len() > 1 => route.responses.pop_front().unwrap(),
+            Some(route) => route.responses[0].clone(),
+            None => Response::new(StatusCode::NOT_FOUND, ""),
+        };
+        Ok(response)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::MemoryTransport;
+    use crate::transport::{Request, Response, Transport};
+    use reqwest::{Method, StatusCode, Url};
+
+    fn get(url: &str) -> Request {
+        Request::new(Method::GET, Url::parse(url).unwrap())
+    }
+
+    #[tokio::test]
+    async fn memory_transport_matches_path_and_query() {
+        let transport = MemoryTransport::new();
+        transport.respond(
+            Method::GET,
+            "/data/results/get?subsession_id=1",
+            Response::new(StatusCode::OK, "one"),
+        );
+
+        let response = transport
+            .send(get("https://members-ng.iracing.com/data/results/get?subsession_id=1&include_licenses=false"))
+            .await
+            .unwrap();
+        assert_eq!(response.body, b"one");
+
+        let response = transport
+            .send(get(
+                "https://members-ng.iracing.com/data/results/get?subsession_id=2",
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status, StatusCode::NOT_FOUND);
+        assert_eq!(transport.requests().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn memory_transport_serves_responses_in_order() {
+        let transport = MemoryTransport::new();
+        transport.respond(
+            Method::GET,
+            "/data/car/get",
+            Response::new(StatusCode::UNAUTHORIZED, ""),
+        );
+        transport.respond(
+            Method::GET,
+            "/data/car/get",
+            Response::new(StatusCode::OK, ""),
+        );
+
+        let url = "https://members-ng.iracing.com/data/car/get";
+        let statuses = [
+            transport.send(get(url)).await.unwrap().status,
+            transport.send(get(url)).await.unwrap().status,
+            transport.send(get(url)).await.unwrap().status,
+        ];
+        assert_eq!(
+            statuses,
+            [StatusCode::UNAUTHORIZED, StatusCode::OK, StatusCode::OK]
+        );
+    }
+}